@@ -16,7 +16,17 @@ impl<'context> Modules<'context> {
     }
 
     pub fn by_name<'name>(self, name: Cow<'name, str>) -> Module<'context, 'name> {
-        Module::new(self, name)
+        Module::new(self, None, name)
+    }
+
+    /// Gets the location to prepare the module `name` before moving it to [`Modules::by_name()`].
+    pub fn staging<'name>(self, name: Cow<'name, str>) -> Module<'context, 'name> {
+        Module::new(self, Some(".staging"), name)
+    }
+
+    /// Gets the location to keep the previous version of the module `name`.
+    pub fn previous<'name>(self, name: Cow<'name, str>) -> Module<'context, 'name> {
+        Module::new(self, Some(".previous"), name)
     }
 
     pub fn path(&self) -> PathBuf {
@@ -41,6 +51,7 @@ pub trait ModuleDirectory {
 #[derive(Directory)]
 pub struct Module<'context, 'name> {
     parent: Modules<'context>,
+    group: Option<&'static str>,
     name: Cow<'name, str>,
 
     #[directory(name = ".locenv", pub)]
@@ -48,9 +59,10 @@ pub struct Module<'context, 'name> {
 }
 
 impl<'context, 'name> Module<'context, 'name> {
-    fn new(parent: Modules<'context>, name: Cow<'name, str>) -> Self {
+    fn new(parent: Modules<'context>, group: Option<&'static str>, name: Cow<'name, str>) -> Self {
         Self {
             parent,
+            group,
             name,
             metadata: PhantomData,
         }
@@ -58,6 +70,11 @@ impl<'context, 'name> Module<'context, 'name> {
 
     pub fn path(&self) -> PathBuf {
         let mut path = self.parent.path();
+
+        if let Some(v) = self.group {
+            path.push(v);
+        }
+
         path.push(self.name.as_ref());
         path
     }
@@ -65,9 +82,7 @@ impl<'context, 'name> Module<'context, 'name> {
 
 impl<'context, 'name> ModuleDirectory for Module<'context, 'name> {
     fn path(&self) -> PathBuf {
-        let mut path = self.parent.path();
-        path.push(self.name.as_ref());
-        path
+        Module::path(self)
     }
 }
 
@@ -132,3 +147,61 @@ impl<'context, 'name> ModuleConfigurations<'context, 'name> {
         path
     }
 }
+
+/// Represents where to cache the repositories that shared between all projects.
+pub struct Repositories<'context> {
+    parent: Datas<'context>,
+    name: &'static str,
+}
+
+impl<'context> Repositories<'context> {
+    pub(super) fn new(parent: Datas<'context>, name: &'static str) -> Self {
+        Self { parent, name }
+    }
+
+    pub fn by_name<'name>(self, name: Cow<'name, str>) -> Repository<'context, 'name> {
+        Repository::new(self, name)
+    }
+
+    pub fn path(&self) -> PathBuf {
+        let mut path = self.parent.path();
+        path.push(self.name);
+        path
+    }
+}
+
+/// Represents a cached repository.
+pub struct Repository<'context, 'name> {
+    parent: Repositories<'context>,
+    name: Cow<'name, str>,
+}
+
+impl<'context, 'name> Repository<'context, 'name> {
+    fn new(parent: Repositories<'context>, name: Cow<'name, str>) -> Self {
+        Self { parent, name }
+    }
+
+    pub fn path(&self) -> PathBuf {
+        let mut path = self.parent.path();
+        path.push(self.name.as_ref());
+        path
+    }
+}
+
+/// Represents the location of the public keys to verify module packages.
+pub struct TrustedKeys<'context> {
+    parent: Datas<'context>,
+    name: &'static str,
+}
+
+impl<'context> TrustedKeys<'context> {
+    pub(super) fn new(parent: Datas<'context>, name: &'static str) -> Self {
+        Self { parent, name }
+    }
+
+    pub fn path(&self) -> PathBuf {
+        let mut path = self.parent.path();
+        path.push(self.name);
+        path
+    }
+}
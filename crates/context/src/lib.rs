@@ -1,6 +1,7 @@
 use dirtree_macros::Directory;
 use std::env::VarError;
 use std::error::Error;
+use std::ffi::{OsStr, OsString};
 use std::fmt::{Display, Formatter};
 use std::marker::PhantomData;
 use std::path::{Path, PathBuf};
@@ -8,10 +9,16 @@ use std::path::{Path, PathBuf};
 pub mod data;
 pub mod runtime;
 
+/// Default name of the file that define the services of the project.
+pub const SERVICES_FILE: &str = "locenv-services.yml";
+
 /// Represents a context to run locenv.
 pub struct Context {
     project: PathBuf,
+    services: OsString,
     data: PathBuf,
+    offline: bool,
+    insecure: bool,
 }
 
 impl Context {
@@ -23,7 +30,22 @@ impl Context {
         let data = match std::env::var(var) {
             Ok(r) => PathBuf::from(r),
             Err(e) => match e {
-                VarError::NotPresent => return Err(ContextError::NoPrefixEnv(var.into())),
+                VarError::NotPresent => match dirs::data_dir() {
+                    Some(v) => v.join("locenv"),
+                    None => return Err(ContextError::NoDataDirectory(var.into())),
+                },
+                VarError::NotUnicode(_) => {
+                    return Err(ContextError::PrefixEnvNotUnicode(var.into()))
+                }
+            },
+        };
+
+        // Check if we are in offline mode.
+        let var = "LOCENV_OFFLINE";
+        let offline = match std::env::var(var) {
+            Ok(r) => !matches!(r.as_str(), "" | "0" | "false"),
+            Err(e) => match e {
+                VarError::NotPresent => false,
                 VarError::NotUnicode(_) => {
                     return Err(ContextError::PrefixEnvNotUnicode(var.into()))
                 }
@@ -31,12 +53,58 @@ impl Context {
         };
 
         // Construct context.
-        Ok(Self { project, data })
+        Ok(Self {
+            project,
+            services: SERVICES_FILE.into(),
+            data,
+            offline,
+            insecure: false,
+        })
+    }
+
+    /// Find the nearest directory that contains `locenv-services.yml`, starting from `path` up to the root.
+    pub fn find_project<P: AsRef<Path>>(path: P) -> Option<PathBuf> {
+        path.as_ref()
+            .ancestors()
+            .find(|p| p.join(SERVICES_FILE).is_file())
+            .map(|p| p.to_owned())
+    }
+
+    /// Use `file` to define the services instead of `locenv-services.yml`. The project will be the
+    /// directory that contains `file`.
+    pub fn set_project_file<P: AsRef<Path>>(&mut self, file: P) {
+        let file = file.as_ref();
+
+        if let Some(v) = file.parent() {
+            self.project = v.to_owned();
+        }
+
+        if let Some(v) = file.file_name() {
+            self.services = v.to_owned();
+        }
+    }
+
+    /// Gets a value indicating whether network access is not allowed.
+    pub fn offline(&self) -> bool {
+        self.offline
+    }
+
+    pub fn set_offline(&mut self, offline: bool) {
+        self.offline = offline;
+    }
+
+    /// Gets a value indicating whether module packages can be installed without verification.
+    pub fn insecure(&self) -> bool {
+        self.insecure
+    }
+
+    pub fn set_insecure(&mut self, insecure: bool) {
+        self.insecure = insecure;
     }
 
     /// Gets the current project.
     pub fn project(&self) -> Project {
-        Project::new(&self.project)
+        Project::new(&self.project, &self.services)
     }
 
     /// Get the global data for locenv.
@@ -49,26 +117,34 @@ impl Context {
 #[derive(Directory)]
 pub struct Project<'context> {
     path: &'context Path,
+    services: &'context OsStr,
 
     #[directory(pub, name = ".locenv")]
     runtime: PhantomData<self::runtime::Runtime<'context>>,
-
-    #[placeholder(pub, name = "locenv-services.yml")]
-    services: PhantomData<()>,
 }
 
 impl<'context> Project<'context> {
-    fn new(path: &'context Path) -> Self {
+    fn new(path: &'context Path, services: &'context OsStr) -> Self {
         Project {
             path,
+            services,
             runtime: PhantomData,
-            services: PhantomData,
         }
     }
 
     pub fn path(&self) -> PathBuf {
         self.path.into()
     }
+
+    /// Gets the path to the file that define the services.
+    pub fn services(&self) -> PathBuf {
+        self.path.join(self.services)
+    }
+
+    /// Gets the path to the lockfile, which is the services file with `.lock` extension.
+    pub fn services_lock(&self) -> PathBuf {
+        self.services().with_extension("lock")
+    }
 }
 
 /// Represents locenv global data.
@@ -81,6 +157,12 @@ pub struct Datas<'context> {
 
     #[directory(pub)]
     config: PhantomData<self::data::Configurations<'context>>,
+
+    #[directory(pub)]
+    repository: PhantomData<self::data::Repositories<'context>>,
+
+    #[directory(pub, name = "trusted-keys")]
+    trusted_keys: PhantomData<self::data::TrustedKeys<'context>>,
 }
 
 impl<'context> Datas<'context> {
@@ -89,6 +171,8 @@ impl<'context> Datas<'context> {
             path,
             module: PhantomData,
             config: PhantomData,
+            repository: PhantomData,
+            trusted_keys: PhantomData,
         }
     }
 
@@ -100,7 +184,7 @@ impl<'context> Datas<'context> {
 /// Represents the error when instantiate the context.
 #[derive(Debug)]
 pub enum ContextError {
-    NoPrefixEnv(String),
+    NoDataDirectory(String),
     PrefixEnvNotUnicode(String),
 }
 
@@ -109,7 +193,11 @@ impl Error for ContextError {}
 impl Display for ContextError {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
         match self {
-            Self::NoPrefixEnv(v) => write!(f, "No environment variable {} has been set", v),
+            Self::NoDataDirectory(v) => write!(
+                f,
+                "Cannot determine the data directory for this platform, please set {} instead",
+                v
+            ),
             Self::PrefixEnvNotUnicode(v) => write!(
                 f,
                 "Environment variable {} contains invalid unicode data",
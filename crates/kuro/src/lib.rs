@@ -72,6 +72,10 @@ where
         headers.append(&format!("Accept: {}", v)).unwrap();
     }
 
+    if let Some(v) = custom.authorization {
+        headers.append(&format!("Authorization: {}", v)).unwrap();
+    }
+
     client.http_headers(headers).unwrap();
 }
 
@@ -134,6 +138,7 @@ pub struct Headers<'endpoint> {
     pub content_length: Option<u64>,
     pub user_agent: Option<&'endpoint str>,
     pub accept: Option<&'endpoint str>,
+    pub authorization: Option<&'endpoint str>,
 }
 
 /// Represetns a status line of the response.
@@ -0,0 +1,42 @@
+use super::{validate_configuration, Command};
+use crate::SUCCESS;
+use context::Context;
+
+pub(super) const COMMAND: Command = Command {
+    name: "config",
+    specs,
+    run,
+    service_manager_state: None,
+};
+
+pub const OPEN_CONFIGURATION_FAILED: u8 = 1;
+pub const INVALID_CONFIGURATION: u8 = 2;
+
+fn specs(name: &str) -> clap::Command<'static> {
+    let validate = clap::Command::new("validate").about("Check the configurations for errors");
+
+    clap::Command::new(name)
+        .about("Manage the configurations")
+        .subcommand_required(true)
+        .subcommand(validate)
+}
+
+fn run(context: &Context, args: &clap::ArgMatches) -> u8 {
+    if args.subcommand_matches("validate").is_some() {
+        let path = context.project().services();
+
+        match validate_configuration(&path) {
+            Ok(true) => {
+                println!("{} is valid", path.display());
+                SUCCESS
+            }
+            Ok(false) => INVALID_CONFIGURATION,
+            Err(e) => {
+                eprintln!("Failed to open {}: {}", path.display(), e);
+                OPEN_CONFIGURATION_FAILED
+            }
+        }
+    } else {
+        panic!("Sub-command not implemented")
+    }
+}
@@ -0,0 +1,39 @@
+use super::Command;
+use crate::SUCCESS;
+use context::Context;
+
+pub(super) const COMMAND: Command = Command {
+    name: "info",
+    specs: |name| clap::Command::new(name).about("Show the paths that locenv is using"),
+    run,
+    service_manager_state: None,
+};
+
+fn run(context: &Context, _: &clap::ArgMatches) -> u8 {
+    let project = context.project();
+
+    println!("Version       : {}", env!("CARGO_PKG_VERSION"));
+    println!("Project       : {}", project.path().display());
+    println!("Services      : {}", project.services().display());
+    println!("Lockfile      : {}", project.services_lock().display());
+    println!("Data          : {}", context.data().path().display());
+    println!(
+        "Modules       : {}",
+        context.data().module(false).unwrap().path().display()
+    );
+    println!(
+        "Configurations: {}",
+        context.data().config(false).unwrap().path().display()
+    );
+    println!(
+        "Repositories  : {}",
+        context.data().repository(false).unwrap().path().display()
+    );
+    println!(
+        "Trusted keys  : {}",
+        context.data().trusted_keys(false).unwrap().path().display()
+    );
+    println!("Offline       : {}", context.offline());
+
+    SUCCESS
+}
@@ -0,0 +1,103 @@
+use super::Command;
+use crate::SUCCESS;
+use clap::{Arg, ArgAction};
+use context::{Context, SERVICES_FILE};
+use module::Module;
+use std::borrow::Cow;
+use std::fs::OpenOptions;
+use std::io::{ErrorKind, Write};
+use std::path::PathBuf;
+
+pub(super) const COMMAND: Command = Command {
+    name: "init",
+    specs: |name| {
+        clap::Command::new(name)
+            .about("Create a starter locenv-services.yml")
+            .arg(
+                Arg::new("service")
+                    .help("Create locenv-service.yml for the service in the current directory instead")
+                    .long("service")
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("template")
+                    .help("Name of the installed module to get the template from")
+                    .long("template")
+                    .value_name("MODULE"),
+            )
+    },
+    run,
+    service_manager_state: None,
+};
+
+pub const ALREADY_EXISTS: u8 = 1;
+pub const WRITE_FAILED: u8 = 2;
+pub const FIND_MODULE_FAILED: u8 = 3;
+pub const READ_TEMPLATE_FAILED: u8 = 4;
+
+const SERVICE_FILE: &str = "locenv-service.yml";
+
+fn run(context: &Context, args: &clap::ArgMatches) -> u8 {
+    let cwd = std::env::current_dir().unwrap();
+    let service = *args.get_one::<bool>("service").unwrap();
+
+    // Get the file to create. We don't use the discovered project unless it was specified explicitly
+    // because the user may want to create a new project inside the other one.
+    let (path, name) = if service {
+        (cwd.join(SERVICE_FILE), SERVICE_FILE)
+    } else if args.contains_id("file") || args.contains_id("project-dir") {
+        (context.project().services(), SERVICES_FILE)
+    } else {
+        (cwd.join(SERVICES_FILE), SERVICES_FILE)
+    };
+
+    // Get the template.
+    let template: Cow<'static, [u8]> = match args.get_one::<String>("template") {
+        Some(v) => {
+            let module = match Module::find(context, Cow::Borrowed(v)) {
+                Ok(r) => r,
+                Err(e) => {
+                    eprintln!("Failed to find module '{}': {}", v, e);
+                    return FIND_MODULE_FAILED;
+                }
+            };
+
+            let file: PathBuf = module.path().join("templates").join(name);
+
+            match std::fs::read(&file) {
+                Ok(r) => Cow::Owned(r),
+                Err(e) => {
+                    eprintln!("Failed to read {}: {}", file.display(), e);
+                    return READ_TEMPLATE_FAILED;
+                }
+            }
+        }
+        None => Cow::Borrowed(if service {
+            include_bytes!("service.yml")
+        } else {
+            include_bytes!("services.yml")
+        }),
+    };
+
+    // Write the file.
+    let mut file = match OpenOptions::new().write(true).create_new(true).open(&path) {
+        Ok(r) => r,
+        Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+            eprintln!("{} already exists", path.display());
+            return ALREADY_EXISTS;
+        }
+        Err(e) => {
+            eprintln!("Failed to create {}: {}", path.display(), e);
+            return WRITE_FAILED;
+        }
+    };
+
+    if let Err(e) = file.write_all(&template) {
+        eprintln!("Failed to write {}: {}", path.display(), e);
+        return WRITE_FAILED;
+    }
+
+    println!("Created {}", path.display());
+
+    SUCCESS
+}
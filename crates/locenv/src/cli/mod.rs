@@ -1,10 +1,22 @@
-use clap::ArgMatches;
+use ::module::{InstallError, Module, PackageId, RequireError, UpdateError};
+use clap::{value_parser, Arg, ArgAction, ArgMatches};
 use context::Context;
 use dirtree::File;
+use service::lock::{LockedModule, Lockfile};
+use service::repository;
+use service::ServiceConfigurations;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use std::unreachable;
 
+mod config;
+mod info;
+mod init;
 mod module;
+mod progress;
 mod pull;
+mod schema;
 mod stop;
 mod up;
 
@@ -20,6 +32,48 @@ enum ServiceManagerState {
     Running,
 }
 
+/// Exit codes for the Git failures, which have different values on each command.
+struct GitExitCodes {
+    clone_failed: u8,
+    open_failed: u8,
+    pull_failed: u8,
+}
+
+/// Represents a failure from [`service::repository`].
+enum RepositoryError {
+    Download(repository::DownloadError),
+    Update(repository::UpdateError),
+}
+
+impl From<repository::DownloadError> for RepositoryError {
+    fn from(e: repository::DownloadError) -> Self {
+        Self::Download(e)
+    }
+}
+
+impl From<repository::UpdateError> for RepositoryError {
+    fn from(e: repository::UpdateError) -> Self {
+        Self::Update(e)
+    }
+}
+
+// Exit codes that shared between the commands that download the repositories.
+pub const INVALID_REPOSITORY_OPTION: u8 = 3;
+pub const INVALID_REPOSITORY_URI: u8 = 7;
+pub const LOCAL_NOT_FOUND: u8 = 8;
+pub const LINK_FAILED: u8 = 9;
+pub const UNKNOWN_ARCHIVE_FORMAT: u8 = 10;
+pub const ARCHIVE_DOWNLOAD_FAILED: u8 = 11;
+pub const ARCHIVE_CHECKSUM_MISMATCH: u8 = 12;
+pub const ARCHIVE_EXTRACT_FAILED: u8 = 13;
+pub const GIT_SUBMODULE_FAILED: u8 = 14;
+pub const GIT_LFS_NOT_SUPPORTED: u8 = 15;
+pub const GIT_CACHE_FAILED: u8 = 16;
+pub const OFFLINE_UNAVAILABLE: u8 = 17;
+pub const GIT_REVISION_NOT_FOUND: u8 = 20;
+pub const OPEN_DEFINITION_FAILED: u8 = 50;
+pub const READ_DEFINITION_FAILED: u8 = 51;
+
 pub const SERVICE_MANAGER_NOT_RUNNING: u8 = 252;
 pub const SERVICE_MANAGER_RUNNING: u8 = 253;
 pub const INITIALIZATION_FAILED: u8 = 254;
@@ -27,15 +81,25 @@ pub const INITIALIZATION_FAILED: u8 = 254;
 pub fn run() -> u8 {
     // Set up commands.
     let commands = [
+        &self::config::COMMAND,
+        &self::info::COMMAND,
+        &self::init::COMMAND,
         &self::module::COMMAND,
         &self::pull::COMMAND,
+        &self::schema::COMMAND,
         &self::stop::COMMAND,
         &self::up::COMMAND,
     ];
 
     // Parse arguments.
     let args = parse_command_line(&commands);
-    let context = match Context::new(std::env::current_dir().unwrap()) {
+    let cwd = std::env::current_dir().unwrap();
+    let project = match args.get_one::<PathBuf>("project-dir") {
+        Some(v) => cwd.join(v),
+        None => Context::find_project(&cwd).unwrap_or_else(|| cwd.clone()),
+    };
+
+    let mut context = match Context::new(project) {
         Ok(r) => r,
         Err(e) => {
             eprintln!("{}", e);
@@ -43,12 +107,57 @@ pub fn run() -> u8 {
         }
     };
 
+    if let Some(file) = args.get_one::<PathBuf>("file") {
+        context.set_project_file(cwd.join(file));
+    }
+
+    if *args.get_one::<bool>("offline").unwrap() {
+        context.set_offline(true);
+    }
+
+    if *args.get_one::<bool>("insecure").unwrap() {
+        context.set_insecure(true);
+    }
+
     // Run command.
     process_command_line(&context, &commands, &args)
 }
 
 fn parse_command_line(commands: &[&Command]) -> ArgMatches {
-    let mut args = clap::command!().subcommand_required(true);
+    let mut args = clap::command!()
+        .subcommand_required(true)
+        .arg(
+            Arg::new("offline")
+                .help("Do not access the network (can also be enabled with LOCENV_OFFLINE)")
+                .long("offline")
+                .global(true)
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("insecure")
+                .help("Install module packages without verifying their checksum and signature")
+                .long("insecure")
+                .global(true)
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("project-dir")
+                .help("Path to the project (default to the nearest directory that contains locenv-services.yml)")
+                .long("project-dir")
+                .value_name("DIR")
+                .global(true)
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("file")
+                .help("Path to the file that define the services, instead of locenv-services.yml")
+                .short('f')
+                .long("file")
+                .value_name("FILE")
+                .global(true)
+                .conflicts_with("project-dir")
+                .value_parser(value_parser!(PathBuf)),
+        );
 
     for command in commands {
         args = args.subcommand((command.specs)(command.name));
@@ -94,3 +203,301 @@ fn process_command_line(context: &Context, commands: &[&Command], args: &ArgMatc
 
     unreachable!();
 }
+
+/// Gets the names of configurations that need to be downloaded but cannot be done in offline mode.
+/// The configuration that already downloaded is also included if it need to be moved to the revision
+/// in `lock` that is not available on the machine.
+fn unavailable_offline<'a, I>(
+    context: &Context,
+    configurations: I,
+    lock: Option<&Lockfile>,
+) -> Vec<&'a str>
+where
+    I: IntoIterator<Item = (&'a String, &'a ServiceConfigurations)>,
+{
+    let mut names: Vec<&str> = Vec::new();
+
+    for (name, config) in configurations {
+        let path = context
+            .project()
+            .runtime(false)
+            .unwrap()
+            .configurations(false)
+            .unwrap()
+            .by_name(Cow::Borrowed(name.as_str()))
+            .path();
+
+        if !path.exists() {
+            if !service::repository::available_offline(context, &config.repository) {
+                names.push(name);
+            }
+
+            continue;
+        }
+
+        // Check if the locked revision can be checked out.
+        let revision = match lock.and_then(|l| l.revision(name, &config.repository)) {
+            Some(v) => v,
+            None => continue,
+        };
+
+        if service::repository::revision(&config.repository, &path).as_deref() != Some(revision)
+            && !service::repository::revision_available_offline(&config.repository, &path, revision)
+        {
+            names.push(name);
+        }
+    }
+
+    names.sort();
+    names
+}
+
+/// Make sure all `modules` are installed and record its version in `locked`. The version in `lock` will
+/// be used if the identifier was not changed. The relative paths in `modules` are relative to `base`.
+/// Returns `false` if some modules cannot be installed.
+fn require_modules(
+    context: &Context,
+    modules: &BTreeMap<String, PackageId>,
+    base: &Path,
+    lock: Option<&Lockfile>,
+    locked: &mut Lockfile,
+    update: bool,
+) -> bool {
+    for (name, id) in modules {
+        // Use the locked version if available.
+        let version = lock
+            .and_then(|l| l.module_version(name, id))
+            .and_then(|v| v.parse::<::module::Version>().ok());
+        let required = id.relative_to(base);
+
+        let module = match Module::require(context, name, &required, version.as_ref(), update) {
+            Ok(r) => r,
+            Err(e) => {
+                match e {
+                    RequireError::FindFailed(e) => eprintln!("{}", e),
+                    RequireError::InstallFailed(e) => match e {
+                        InstallError::InvalidIdentifier => eprintln!(
+                            "'{}' is not a valid identifer for '{}'",
+                            id.name(),
+                            id.registry()
+                        ),
+                        InstallError::GetPackageFailed(e) => {
+                            eprintln!("Failed to get a package for module '{}': {}", name, e)
+                        }
+                        InstallError::VerifyFailed(e) => eprintln!(
+                            "Failed to verify the package for module '{}': {} (use --insecure to skip the verification)",
+                            name, e
+                        ),
+                        InstallError::AlreadyInstalled(n) => {
+                            eprintln!("'{}' provides module '{}', not '{}'", id, n, name)
+                        }
+                        InstallError::Offline => {
+                            eprintln!("Module '{}' cannot be installed in offline mode", name)
+                        }
+                        InstallError::DependencyFailed(e) => {
+                            eprintln!("Failed to install module '{}': {}", name, e)
+                        }
+                        InstallError::VersionMismatch(v) => eprintln!(
+                            "'{}' provides version {} of module '{}', which does not match",
+                            id, v, name
+                        ),
+                        InstallError::ExtractFailed(_)
+                        | InstallError::InvalidDefinition(_)
+                        | InstallError::StagingFailed(_)
+                        | InstallError::CopyFailed(_)
+                        | InstallError::MoveFailed(_) => {
+                            eprintln!("Failed to install module '{}': {}", name, e)
+                        }
+                    },
+                    RequireError::UpdateFailed(e) => match e {
+                        UpdateError::GetPackageFailed(e) => {
+                            eprintln!("Failed to get a package for module '{}': {}", name, e)
+                        }
+                        UpdateError::VerifyFailed(e) => eprintln!(
+                            "Failed to verify the package for module '{}': {} (use --insecure to skip the verification)",
+                            name, e
+                        ),
+                        UpdateError::Offline => eprintln!(
+                            "Module '{}' need to be updated to {} but cannot be done in offline mode",
+                            name, required
+                        ),
+                        UpdateError::DependencyFailed(e) => {
+                            eprintln!("Failed to update module '{}': {}", name, e)
+                        }
+                        UpdateError::VersionMismatch(v) => eprintln!(
+                            "'{}' provides version {} of module '{}', which does not match",
+                            required, v, name
+                        ),
+                        UpdateError::LoadDefinitionFailed(_, _)
+                        | UpdateError::LoadRegistryFailed(_)
+                        | UpdateError::ExtractFailed(_)
+                        | UpdateError::InvalidDefinition(_)
+                        | UpdateError::CopyFailed(_)
+                        | UpdateError::ReplaceFailed(_) => {
+                            eprintln!("Failed to update module '{}': {}", name, e)
+                        }
+                        UpdateError::NotInstalled | UpdateError::AlreadyLatest => unreachable!(),
+                    },
+                    RequireError::NameMismatch(n) => {
+                        eprintln!("'{}' provides module '{}', not '{}'", id, n, name)
+                    }
+                }
+
+                return false;
+            }
+        };
+
+        let entry = LockedModule {
+            id: id.to_string(),
+            version: module.definition().version.to_string(),
+        };
+
+        locked.modules.insert(name.clone(), entry);
+    }
+
+    true
+}
+
+/// Validate the application configuration on `path` and print all problems that was found. Returns
+/// `false` if the configuration is not valid.
+fn validate_configuration(path: &Path) -> std::io::Result<bool> {
+    let diagnostics = service::validation::validate(path)?;
+
+    for d in &diagnostics {
+        eprintln!("{}", d);
+    }
+
+    Ok(diagnostics.is_empty())
+}
+
+/// Print `error` from downloading or updating the repository on `path` for configuration `name`.
+/// Returns the exit code for it.
+fn repository_failed(name: &str, path: &Path, error: RepositoryError, git: &GitExitCodes) -> u8 {
+    use RepositoryError::{Download, Update};
+
+    match error {
+        Download(repository::DownloadError::Offline) => {
+            eprintln!("Configuration '{}' is not available in offline mode", name);
+            OFFLINE_UNAVAILABLE
+        }
+        Download(repository::DownloadError::InvalidUri)
+        | Update(repository::UpdateError::InvalidUri) => {
+            eprintln!("Invalid repository URI for configuration '{}'", name);
+            INVALID_REPOSITORY_URI
+        }
+        Download(repository::DownloadError::InvalidOption(option))
+        | Update(repository::UpdateError::InvalidOption(option)) => {
+            eprintln!("Invalid value for repository option '{}'", option);
+            INVALID_REPOSITORY_OPTION
+        }
+        Download(repository::DownloadError::GitCloneFailed(e)) => {
+            eprintln!("Failed to clone the repository: {}", e);
+            git.clone_failed
+        }
+        Update(repository::UpdateError::GitOpenFailed(e)) => {
+            eprintln!(
+                "Failed to open {} as a Git repository: {}",
+                path.display(),
+                e
+            );
+            git.open_failed
+        }
+        Update(repository::UpdateError::GitFindOriginFailed(e)) => {
+            eprintln!(
+                "Failed to find 'origin' remote on repository {}: {}",
+                path.display(),
+                e
+            );
+            git.pull_failed
+        }
+        Update(repository::UpdateError::GitFetchOriginFailed(e)) => {
+            eprintln!("Failed to pull {}: {}", path.display(), e);
+            git.pull_failed
+        }
+        Download(repository::DownloadError::GitCacheUnavailable(e))
+        | Update(repository::UpdateError::GitCacheUnavailable(e)) => {
+            eprintln!(
+                "Failed to lock the cached repository for configuration '{}': {}",
+                name, e
+            );
+            GIT_CACHE_FAILED
+        }
+        Download(repository::DownloadError::GitCacheFailed(e))
+        | Update(repository::UpdateError::GitCacheFailed(e)) => {
+            eprintln!(
+                "Failed to update the cached repository for configuration '{}': {}",
+                name, e
+            );
+            GIT_CACHE_FAILED
+        }
+        Download(repository::DownloadError::GitRevisionNotFound(r))
+        | Update(repository::UpdateError::GitRevisionNotFound(r)) => {
+            eprintln!(
+                "Revision {} for configuration '{}' does not exist in the repository",
+                r, name
+            );
+            GIT_REVISION_NOT_FOUND
+        }
+        Download(repository::DownloadError::GitSubmoduleFailed(e))
+        | Update(repository::UpdateError::GitSubmoduleFailed(e)) => {
+            eprintln!("Failed to update submodules of {}: {}", path.display(), e);
+            GIT_SUBMODULE_FAILED
+        }
+        Download(repository::DownloadError::GitLfsNotSupported(files))
+        | Update(repository::UpdateError::GitLfsNotSupported(files)) => {
+            eprintln!(
+                "The repository for configuration '{}' contains Git LFS files, which is not supported: {}",
+                name,
+                files.join(", ")
+            );
+            GIT_LFS_NOT_SUPPORTED
+        }
+        Download(repository::DownloadError::LocalNotFound(p))
+        | Update(repository::UpdateError::LocalNotFound(p)) => {
+            eprintln!("Directory {} does not exist", p.display());
+            LOCAL_NOT_FOUND
+        }
+        Download(repository::DownloadError::LinkFailed(e))
+        | Update(repository::UpdateError::LinkFailed(e)) => {
+            eprintln!("Failed to link {}: {}", path.display(), e);
+            LINK_FAILED
+        }
+        Download(repository::DownloadError::ArchiveFormatUnknown)
+        | Update(repository::UpdateError::ArchiveFormatUnknown) => {
+            eprintln!("Cannot determine archive format for configuration '{}', please specify it with 'format' option", name);
+            UNKNOWN_ARCHIVE_FORMAT
+        }
+        Download(repository::DownloadError::ArchiveDownloadFailed(e))
+        | Update(repository::UpdateError::ArchiveDownloadFailed(e)) => {
+            eprintln!("Failed to download the archive: {}", e);
+            ARCHIVE_DOWNLOAD_FAILED
+        }
+        Download(repository::DownloadError::ArchiveChecksumMismatch(expected, actual))
+        | Update(repository::UpdateError::ArchiveChecksumMismatch(expected, actual)) => {
+            eprintln!(
+                "Checksum of the archive for configuration '{}' is {}, expected {}",
+                name, actual, expected
+            );
+            ARCHIVE_CHECKSUM_MISMATCH
+        }
+        Download(repository::DownloadError::ArchiveExtractFailed(e))
+        | Update(repository::UpdateError::ArchiveExtractFailed(e)) => {
+            eprintln!("Failed to extract the archive to {}: {}", path.display(), e);
+            ARCHIVE_EXTRACT_FAILED
+        }
+    }
+}
+
+/// Print `error` from loading the service definition on `path`. Returns the exit code for it.
+fn definition_failed(path: &Path, error: yaml::FileError) -> u8 {
+    match error {
+        yaml::FileError::OpenFailed(e) => {
+            eprintln!("Failed to open {}: {}", path.display(), e);
+            OPEN_DEFINITION_FAILED
+        }
+        yaml::FileError::ParseFailed(e) => {
+            eprintln!("Failed to read {}: {}", path.display(), e);
+            READ_DEFINITION_FAILED
+        }
+    }
+}
@@ -1,8 +1,9 @@
 use super::{Command, ServiceManagerState};
 use crate::SUCCESS;
-use clap::{value_parser, Arg};
+use clap::{value_parser, Arg, ArgAction};
 use context::Context;
-use module::{InstallError, Module, PackageId, UpdateError};
+use module::definition::{BinaryFiles, Program};
+use module::{FindError, InstallError, Module, PackageId, RemoveError, RollbackError, UpdateError};
 use std::borrow::Cow;
 
 pub(super) const COMMAND: Command = Command {
@@ -16,11 +17,21 @@ pub const INVALID_IDENTIFIER: u8 = 1;
 pub const GET_PACKAGE_FAILED: u8 = 2;
 pub const ALREADY_INSTALLED: u8 = 3;
 pub const NOT_INSTALLED: u8 = 4;
+pub const OFFLINE: u8 = 5;
+pub const LIST_FAILED: u8 = 6;
+pub const FIND_FAILED: u8 = 7;
+pub const REMOVE_FAILED: u8 = 8;
+pub const VERIFY_FAILED: u8 = 9;
+pub const DEPENDENCY_FAILED: u8 = 10;
+pub const VERSION_MISMATCH: u8 = 11;
+pub const REPLACE_FAILED: u8 = 12;
+pub const NO_PREVIOUS_VERSION: u8 = 13;
+pub const INVALID_PACKAGE: u8 = 14;
 
 fn specs(name: &str) -> clap::Command<'static> {
     let install = clap::Command::new("install").about("Install a module").arg(
         Arg::new("id")
-            .help("Package identifier (e.g. github:locenv/mod-autoconf)")
+            .help("Package identifier (e.g. github:locenv/mod-autoconf, gitlab:<group>/<project>, gitea:<owner>/<repo>, git:<url>#<ref>, path:<dir> or https://<url>)")
             .required(true)
             .value_parser(value_parser!(PackageId)),
     );
@@ -31,12 +42,44 @@ fn specs(name: &str) -> clap::Command<'static> {
                 .help("Name of the module to update")
                 .required(true),
         );
+    let rollback = clap::Command::new("rollback")
+        .about("Restore the version of installed module before the last update")
+        .arg(
+            Arg::new("name")
+                .help("Name of the module to rollback")
+                .required(true),
+        );
+    let list = clap::Command::new("list").about("List installed modules");
+    let info = clap::Command::new("info")
+        .about("Show the details of installed module")
+        .arg(
+            Arg::new("name")
+                .help("Name of the module to show")
+                .required(true),
+        );
+    let remove = clap::Command::new("remove")
+        .about("Remove installed module")
+        .arg(
+            Arg::new("name")
+                .help("Name of the module to remove")
+                .required(true),
+        )
+        .arg(
+            Arg::new("purge")
+                .help("Remove the configurations of the module too")
+                .long("purge")
+                .action(ArgAction::SetTrue),
+        );
 
     clap::Command::new(name)
         .about("Manage the modules")
         .subcommand_required(true)
         .subcommand(install)
         .subcommand(update)
+        .subcommand(rollback)
+        .subcommand(list)
+        .subcommand(info)
+        .subcommand(remove)
 }
 
 fn run(context: &Context, args: &clap::ArgMatches) -> u8 {
@@ -57,10 +100,41 @@ fn run(context: &Context, args: &clap::ArgMatches) -> u8 {
                     eprintln!("Failed to get a package to install: {}", e);
                     GET_PACKAGE_FAILED
                 }
+                InstallError::VerifyFailed(e) => {
+                    eprintln!("{} (use --insecure to skip the verification)", e);
+                    VERIFY_FAILED
+                }
                 InstallError::AlreadyInstalled(name) => {
                     eprintln!("The module '{}' is already installed", name);
                     ALREADY_INSTALLED
                 }
+                InstallError::Offline => {
+                    eprintln!("Cannot install a module in offline mode");
+                    OFFLINE
+                }
+                InstallError::DependencyFailed(e) => {
+                    eprintln!("{}", e);
+                    DEPENDENCY_FAILED
+                }
+                InstallError::VersionMismatch(v) => {
+                    eprintln!(
+                        "The package is version {}, which does not match the version constraint",
+                        v
+                    );
+                    VERSION_MISMATCH
+                }
+                InstallError::ExtractFailed(_) | InstallError::InvalidDefinition(_) => {
+                    eprintln!("{}", e);
+                    INVALID_PACKAGE
+                }
+                InstallError::StagingFailed(_) | InstallError::CopyFailed(_) => {
+                    eprintln!("{}", e);
+                    REPLACE_FAILED
+                }
+                InstallError::MoveFailed(e) => {
+                    eprintln!("Failed to move the module to the module directory: {}", e);
+                    REPLACE_FAILED
+                }
             }
         } else {
             SUCCESS
@@ -74,19 +148,236 @@ fn run(context: &Context, args: &clap::ArgMatches) -> u8 {
                     eprintln!("The specified module is not installed");
                     NOT_INSTALLED
                 }
+                UpdateError::LoadDefinitionFailed(_, _) | UpdateError::LoadRegistryFailed(_) => {
+                    eprintln!("{}", e);
+                    FIND_FAILED
+                }
                 UpdateError::GetPackageFailed(e) => {
                     eprintln!("Failed to get a package to update: {}", e);
                     GET_PACKAGE_FAILED
                 }
+                UpdateError::VerifyFailed(e) => {
+                    eprintln!("{} (use --insecure to skip the verification)", e);
+                    VERIFY_FAILED
+                }
                 UpdateError::AlreadyLatest => {
                     println!("The specified module is already in latest version");
                     SUCCESS
                 }
+                UpdateError::Offline => {
+                    eprintln!("Cannot update a module in offline mode");
+                    OFFLINE
+                }
+                UpdateError::DependencyFailed(e) => {
+                    eprintln!("{}", e);
+                    DEPENDENCY_FAILED
+                }
+                UpdateError::VersionMismatch(v) => {
+                    eprintln!(
+                        "The package is version {}, which does not match the version constraint",
+                        v
+                    );
+                    VERSION_MISMATCH
+                }
+                UpdateError::ExtractFailed(_) | UpdateError::InvalidDefinition(_) => {
+                    eprintln!("{}", e);
+                    INVALID_PACKAGE
+                }
+                UpdateError::CopyFailed(_) => {
+                    eprintln!("{}", e);
+                    REPLACE_FAILED
+                }
+                UpdateError::ReplaceFailed(e) => {
+                    eprintln!("Failed to replace the installed module: {}", e);
+                    REPLACE_FAILED
+                }
             }
         } else {
             SUCCESS
         }
+    } else if let Some(args) = args.subcommand_matches("rollback") {
+        let name = args.get_one::<String>("name").unwrap();
+
+        match Module::rollback(context, Cow::Borrowed(name)) {
+            Ok(m) => {
+                println!(
+                    "Restored module '{}' to version {}",
+                    name,
+                    m.definition().version
+                );
+                SUCCESS
+            }
+            Err(e) => match e {
+                RollbackError::NotInstalled => {
+                    eprintln!("The specified module is not installed");
+                    NOT_INSTALLED
+                }
+                RollbackError::NoPreviousVersion => {
+                    eprintln!("The specified module does not have a previous version");
+                    NO_PREVIOUS_VERSION
+                }
+                RollbackError::LoadDefinitionFailed(_, _) => {
+                    eprintln!("{}", e);
+                    FIND_FAILED
+                }
+                RollbackError::RequiredBy(_, _) => {
+                    eprintln!("{}", e);
+                    DEPENDENCY_FAILED
+                }
+                RollbackError::ReplaceFailed(_) => {
+                    eprintln!("{}", e);
+                    REPLACE_FAILED
+                }
+            },
+        }
+    } else if args.subcommand_matches("list").is_some() {
+        list(context)
+    } else if let Some(args) = args.subcommand_matches("info") {
+        info(context, args.get_one::<String>("name").unwrap())
+    } else if let Some(args) = args.subcommand_matches("remove") {
+        let name = args.get_one::<String>("name").unwrap();
+        let purge = *args.get_one::<bool>("purge").unwrap();
+
+        match Module::remove(context, name, purge) {
+            Ok(_) => {
+                println!("Removed module '{}'", name);
+                SUCCESS
+            }
+            Err(e) => match e {
+                RemoveError::NotInstalled => {
+                    eprintln!("The specified module is not installed");
+                    NOT_INSTALLED
+                }
+                e => {
+                    eprintln!("{}", e);
+                    REMOVE_FAILED
+                }
+            },
+        }
     } else {
         panic!("Sub-command not implemented")
     }
 }
+
+fn list(context: &Context) -> u8 {
+    let modules = match Module::list(context) {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("Failed to list installed modules: {}", e);
+            return LIST_FAILED;
+        }
+    };
+
+    println!(
+        "{:<20} {:<14} {:<40} PROGRAM",
+        "NAME", "VERSION", "REGISTRY"
+    );
+
+    for module in modules {
+        match module {
+            Ok(m) => {
+                let registry = match m.registry() {
+                    Some(v) => v.to_string(),
+                    None => "-".into(),
+                };
+
+                println!(
+                    "{:<20} {:<14} {:<40} {}",
+                    m.name(),
+                    m.definition().version,
+                    registry,
+                    program_type(&m.definition().program)
+                );
+            }
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+
+    SUCCESS
+}
+
+fn info(context: &Context, name: &str) -> u8 {
+    let module = match Module::find(context, Cow::Borrowed(name)) {
+        Ok(r) => r,
+        Err(e) => {
+            return match e {
+                FindError::NotInstalled(_) => {
+                    eprintln!("The specified module is not installed");
+                    NOT_INSTALLED
+                }
+                e => {
+                    eprintln!("{}", e);
+                    FIND_FAILED
+                }
+            }
+        }
+    };
+
+    let definition = module.definition();
+    let config = context
+        .data()
+        .config(false)
+        .unwrap()
+        .for_module(Cow::Borrowed(name))
+        .path();
+
+    println!("Name          : {}", definition.name);
+    println!("Version       : {}", definition.version);
+
+    match module.registry() {
+        Some(v) => println!("Registry      : {}", v),
+        None => println!("Registry      : -"),
+    }
+
+    println!("Program       : {}", program_type(&definition.program));
+
+    if let Program::Binary(p) = &definition.program {
+        let platforms = [
+            ("linux", &p.linux),
+            ("darwin", &p.darwin),
+            ("win32", &p.win32),
+        ];
+
+        for (platform, files) in platforms {
+            if let Some(files) = files {
+                println!("  {:<12}: {}", platform, cpus(files).join(", "));
+            }
+        }
+    }
+
+    if !definition.dependencies.is_empty() {
+        let dependencies: Vec<String> = definition
+            .dependencies
+            .iter()
+            .map(|(n, id)| format!("{} ({})", n, id))
+            .collect();
+
+        println!("Dependencies  : {}", dependencies.join(", "));
+    }
+
+    println!("Path          : {}", module.path().display());
+    println!("Configurations: {}", config.display());
+
+    SUCCESS
+}
+
+fn program_type(program: &Program) -> &'static str {
+    match program {
+        Program::Script(_) => "script",
+        Program::Binary(_) => "binary",
+    }
+}
+
+fn cpus(files: &BinaryFiles) -> Vec<&'static str> {
+    let mut cpus = Vec::new();
+
+    if files.aarch64.is_some() {
+        cpus.push("aarch64");
+    }
+
+    if files.amd64.is_some() {
+        cpus.push("amd64");
+    }
+
+    cpus
+}
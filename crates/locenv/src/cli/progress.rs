@@ -0,0 +1,125 @@
+use service::repository::TransferProgress;
+use std::io::{IsTerminal, Write};
+use std::time::{Duration, Instant};
+
+/// Width of the progress bar, in characters.
+const BAR_WIDTH: usize = 30;
+
+/// Render [`TransferProgress`] as a progress bar when the stdout is a terminal, otherwise periodic log
+/// lines.
+pub struct ProgressRenderer {
+    terminal: bool,
+    start: Instant,
+    last: Option<Instant>,
+    drawn: bool,
+    completed: bool,
+}
+
+impl ProgressRenderer {
+    pub fn new() -> Self {
+        Self {
+            terminal: std::io::stdout().is_terminal(),
+            start: Instant::now(),
+            last: None,
+            drawn: false,
+            completed: false,
+        }
+    }
+
+    pub fn update(&mut self, progress: &TransferProgress) {
+        // Git still reports the progress while resolving deltas after all objects have been received.
+        if self.completed {
+            return;
+        }
+
+        // Limit how often we write the progress so it does not slow down the transfer.
+        let now = Instant::now();
+        let interval = if self.terminal {
+            Duration::from_millis(100)
+        } else {
+            Duration::from_secs(5)
+        };
+
+        let completed = progress.total_objects > 0
+            && progress.received_objects == progress.total_objects
+            && progress.indexed_objects == progress.total_objects;
+
+        if let Some(last) = self.last {
+            if !completed && now.duration_since(last) < interval {
+                return;
+            }
+        }
+
+        self.last = Some(now);
+        self.completed = completed;
+
+        // Build the progress line.
+        let percent = (progress.received_objects * 100)
+            .checked_div(progress.total_objects)
+            .unwrap_or(0);
+
+        let elapsed = now.duration_since(self.start).as_secs_f64();
+        let throughput = if elapsed > 0.0 {
+            progress.received_bytes as f64 / elapsed
+        } else {
+            0.0
+        };
+
+        let line = format!(
+            "{:>3}% ({}/{} objects), {} | {}/s",
+            percent,
+            progress.received_objects,
+            progress.total_objects,
+            format_bytes(progress.received_bytes as f64),
+            format_bytes(throughput)
+        );
+
+        // Write.
+        let mut stdout = std::io::stdout().lock();
+
+        if self.terminal {
+            let filled = percent * BAR_WIDTH / 100;
+
+            write!(
+                stdout,
+                "\r[{}{}] {}\x1b[K",
+                "#".repeat(filled),
+                " ".repeat(BAR_WIDTH - filled),
+                line
+            )
+            .unwrap();
+        } else {
+            writeln!(stdout, "Received {}", line).unwrap();
+        }
+
+        stdout.flush().unwrap();
+        self.drawn = true;
+    }
+
+    /// Move the cursor to the next line if the progress bar was drawn.
+    pub fn finish(&mut self) {
+        if self.terminal && self.drawn {
+            println!();
+        }
+
+        self.drawn = false;
+        self.completed = false;
+    }
+}
+
+fn format_bytes(bytes: f64) -> String {
+    let units = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes;
+    let mut unit = 0;
+
+    while value >= 1024.0 && unit < units.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} {}", value as u64, units[unit])
+    } else {
+        format!("{:.1} {}", value, units[unit])
+    }
+}
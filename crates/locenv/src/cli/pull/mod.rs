@@ -1,7 +1,12 @@
-use super::{Command, ServiceManagerState};
+use super::progress::ProgressRenderer;
+use super::{
+    definition_failed, repository_failed, require_modules, unavailable_offline,
+    validate_configuration, Command, GitExitCodes, ServiceManagerState, OFFLINE_UNAVAILABLE,
+};
 use crate::SUCCESS;
 use context::Context;
 use dirtree::File;
+use service::lock::{LockedConfiguration, Lockfile};
 use service::{ApplicationConfiguration, ServiceDefinition};
 use std::borrow::Cow;
 use std::time::SystemTime;
@@ -15,34 +20,85 @@ pub(super) const COMMAND: Command = Command {
 
 pub const OPEN_CONFIGURATION_FAILED: u8 = 1;
 pub const READ_CONFIGURATION_FAILED: u8 = 2;
-pub const INVALID_REPOSITORY_OPTION: u8 = 3;
 pub const GIT_OPEN_FAILED: u8 = 4;
 pub const GIT_PULL_FAILED: u8 = 5;
 pub const GIT_CLONE_FAILED: u8 = 6;
-pub const OPEN_DEFINITION_FAILED: u8 = 50;
-pub const READ_DEFINITION_FAILED: u8 = 51;
+pub const READ_LOCK_FAILED: u8 = 18;
+pub const WRITE_LOCK_FAILED: u8 = 19;
+pub const INVALID_CONFIGURATION: u8 = 21;
+pub const REQUIRE_MODULE_FAILED: u8 = 22;
 pub const PLATFORM_NOT_SUPPORTED: u8 = 52;
 pub const BUILD_FAILED: u8 = 53;
 
+const GIT_EXIT_CODES: GitExitCodes = GitExitCodes {
+    clone_failed: GIT_CLONE_FAILED,
+    open_failed: GIT_OPEN_FAILED,
+    pull_failed: GIT_PULL_FAILED,
+};
+
 fn run(context: &Context, _: &clap::ArgMatches) -> u8 {
-    // Load config.
+    // Validate config.
     let path = context.project().services();
-    let config: ApplicationConfiguration = match yaml::load_file(&path) {
+
+    match validate_configuration(&path) {
+        Ok(true) => {}
+        Ok(false) => return INVALID_CONFIGURATION,
+        Err(e) => {
+            eprintln!("Failed to open {}: {}", path.display(), e);
+            return OPEN_CONFIGURATION_FAILED;
+        }
+    }
+
+    // Load config.
+    let config = match ApplicationConfiguration::load(&path) {
         Ok(r) => r,
         Err(e) => {
-            return match e {
-                yaml::FileError::OpenFailed(e) => {
-                    eprintln!("Failed to open {}: {}", path.display(), e);
-                    OPEN_CONFIGURATION_FAILED
-                }
-                yaml::FileError::ParseFailed(e) => {
-                    eprintln!("Failed to read {}: {}", path.display(), e);
-                    READ_CONFIGURATION_FAILED
-                }
-            }
+            eprintln!("Failed to read {}: {}", path.display(), e);
+            return READ_CONFIGURATION_FAILED;
         }
     };
 
+    // Load lockfile.
+    let lock_path = context.project().services_lock();
+    let lock = match Lockfile::load(&lock_path) {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("Failed to read {}: {}", lock_path.display(), e);
+            return READ_LOCK_FAILED;
+        }
+    };
+
+    // Keep the locked modules since the service definitions are not always read.
+    let mut locked = Lockfile {
+        modules: lock.modules.clone(),
+        ..Default::default()
+    };
+
+    // Fail fast if some configurations cannot be downloaded.
+    if context.offline() {
+        let names = unavailable_offline(context, &config.configurations, None);
+
+        if !names.is_empty() {
+            eprintln!(
+                "The following configurations are not available in offline mode: {}",
+                names.join(", ")
+            );
+            return OFFLINE_UNAVAILABLE;
+        }
+    }
+
+    // Install modules required by the project.
+    if !require_modules(
+        context,
+        &config.modules,
+        &context.project().path(),
+        None,
+        &mut locked,
+        true,
+    ) {
+        return REQUIRE_MODULE_FAILED;
+    }
+
     // Update and rebuild repositories.
     for (name, config) in &config.configurations {
         let repo = context
@@ -60,70 +116,57 @@ fn run(context: &Context, _: &clap::ArgMatches) -> u8 {
         if path.exists() {
             println!("Updating {}...", name);
 
-            if let Err(e) = service::repository::update(&config.repository, &path) {
-                return match e {
-                    service::repository::UpdateError::InvalidOption(name) => {
-                        eprintln!("Invalid value for repository option '{}'", name);
-                        INVALID_REPOSITORY_OPTION
-                    }
-                    service::repository::UpdateError::GitOpenFailed(e) => {
-                        eprintln!(
-                            "Failed to open {} as a Git repository: {}",
-                            path.display(),
-                            e
-                        );
-                        GIT_OPEN_FAILED
-                    }
-                    service::repository::UpdateError::GitFindOriginFailed(e) => {
-                        eprintln!(
-                            "Failed to find 'origin' remote on repository {}: {}",
-                            path.display(),
-                            e
-                        );
-                        GIT_PULL_FAILED
-                    }
-                    service::repository::UpdateError::GitFetchOriginFailed(e) => {
-                        eprintln!("Failed to pull {}: {}", path.display(), e);
-                        GIT_PULL_FAILED
-                    }
-                };
-            } else if state.built_time().path().exists() {
-                continue;
+            let mut progress = ProgressRenderer::new();
+            let result = service::repository::update(context, &config.repository, &path, |p| {
+                progress.update(p)
+            });
+
+            progress.finish();
+
+            if let Err(e) = result {
+                return repository_failed(name, &path, e.into(), &GIT_EXIT_CODES);
             }
         } else {
             println!("Downloading {} to {}...", name, path.display());
 
-            if let Err(e) = service::repository::download(&config.repository, &path) {
-                return match e {
-                    service::repository::DownloadError::InvalidOption(name) => {
-                        eprintln!("Invalid value for repository option '{}'", name);
-                        INVALID_REPOSITORY_OPTION
-                    }
-                    service::repository::DownloadError::GitCloneFailed(e) => {
-                        eprintln!("Failed to clone the repository: {}", e);
-                        GIT_CLONE_FAILED
-                    }
-                };
+            let mut progress = ProgressRenderer::new();
+            let result =
+                service::repository::download(context, &config.repository, &path, None, |p| {
+                    progress.update(p)
+                });
+
+            progress.finish();
+
+            if let Err(e) = result {
+                return repository_failed(name, &path, e.into(), &GIT_EXIT_CODES);
             }
         }
 
+        // Record the revision.
+        if let Some(revision) = service::repository::revision(&config.repository, &path) {
+            let entry = LockedConfiguration {
+                uri: config.repository.uri.to_string(),
+                revision,
+            };
+
+            locked.configurations.insert(name.clone(), entry);
+        }
+
+        if state.built_time().path().exists() {
+            continue;
+        }
+
         // Read service definition.
         let service: ServiceDefinition = match yaml::load_file(&service_definition) {
             Ok(r) => r,
-            Err(e) => {
-                return match e {
-                    yaml::FileError::OpenFailed(e) => {
-                        eprintln!("Failed to open {}: {}", service_definition.display(), e);
-                        OPEN_DEFINITION_FAILED
-                    }
-                    yaml::FileError::ParseFailed(e) => {
-                        eprintln!("Failed to read {}: {}", service_definition.display(), e);
-                        READ_DEFINITION_FAILED
-                    }
-                }
-            }
+            Err(e) => return definition_failed(&service_definition, e),
         };
 
+        // Install modules required by the service.
+        if !require_modules(context, &service.modules, &path, None, &mut locked, true) {
+            return REQUIRE_MODULE_FAILED;
+        }
+
         let (config, platform) = match service.flatten() {
             Some(v) => v,
             None => {
@@ -158,5 +201,13 @@ fn run(context: &Context, _: &clap::ArgMatches) -> u8 {
         state.built_time().write(&SystemTime::now()).unwrap();
     }
 
+    // Write lockfile.
+    if !context.offline() && locked != lock {
+        if let Err(e) = locked.save(&lock_path) {
+            eprintln!("Failed to write {}: {}", lock_path.display(), e);
+            return WRITE_LOCK_FAILED;
+        }
+    }
+
     SUCCESS
 }
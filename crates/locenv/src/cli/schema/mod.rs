@@ -0,0 +1,37 @@
+use super::Command;
+use crate::SUCCESS;
+use clap::Arg;
+use context::Context;
+use module::ModuleDefinition;
+use schemars::schema_for;
+use service::{ApplicationConfiguration, ServiceDefinition};
+
+pub(super) const COMMAND: Command = Command {
+    name: "schema",
+    specs: |name| {
+        clap::Command::new(name)
+            .about("Print JSON Schema of the configuration files")
+            .arg(
+                Arg::new("schema")
+                    .help("File to print the schema")
+                    .value_name("FILE")
+                    .required(true)
+                    .value_parser(["services", "service", "module"]),
+            )
+    },
+    run,
+    service_manager_state: None,
+};
+
+fn run(_: &Context, args: &clap::ArgMatches) -> u8 {
+    let schema = match args.get_one::<String>("schema").unwrap().as_str() {
+        "services" => schema_for!(ApplicationConfiguration),
+        "service" => schema_for!(ServiceDefinition),
+        "module" => schema_for!(ModuleDefinition),
+        _ => unreachable!(),
+    };
+
+    println!("{}", serde_json::to_string_pretty(&schema).unwrap());
+
+    SUCCESS
+}
@@ -1,7 +1,13 @@
-use super::{Command, ServiceManagerState};
+use super::progress::ProgressRenderer;
+use super::{
+    definition_failed, repository_failed, require_modules, unavailable_offline,
+    validate_configuration, Command, GitExitCodes, ServiceManagerState, OFFLINE_UNAVAILABLE,
+};
 use crate::SUCCESS;
+use clap::{Arg, ArgAction};
 use context::Context;
 use dirtree::File;
+use service::lock::{LockedConfiguration, Lockfile};
 use service::{ApplicationConfiguration, PlatformConfigurations, ServiceDefinition};
 use std::borrow::Cow;
 use std::collections::HashMap;
@@ -13,48 +19,141 @@ use std::time::SystemTime;
 
 pub(super) const COMMAND: Command = Command {
     name: "up",
-    specs: |name| clap::Command::new(name).about("Start all services"),
+    specs: |name| {
+        clap::Command::new(name).about("Start all services").arg(
+            Arg::new("profile")
+                .help("Start the instances of the specified profile in addition to the instances without profiles")
+                .long("profile")
+                .value_name("NAME")
+                .action(ArgAction::Append),
+        )
+    },
     run,
     service_manager_state: Some(ServiceManagerState::Stopped),
 };
 
 pub const OPEN_CONFIGURATION_FAILED: u8 = 1;
 pub const READ_CONFIGURATION_FAILED: u8 = 2;
-pub const INVALID_REPOSITORY_OPTION: u8 = 3;
 pub const GIT_CLONE_FAILED: u8 = 4;
 pub const GIT_OPEN_FAILED: u8 = 5;
 pub const GIT_PULL_FAILED: u8 = 6;
-pub const OPEN_DEFINITION_FAILED: u8 = 50;
-pub const READ_DEFINITION_FAILED: u8 = 51;
+pub const READ_LOCK_FAILED: u8 = 18;
+pub const WRITE_LOCK_FAILED: u8 = 19;
+pub const INVALID_CONFIGURATION: u8 = 21;
+pub const UNKNOWN_PROFILE: u8 = 22;
+pub const REQUIRE_MODULE_FAILED: u8 = 23;
 pub const PLATFORM_NOT_SUPPORTED: u8 = 52;
 pub const DUPLICATED_CONFIGURATION: u8 = 53;
 pub const BUILD_FAILED: u8 = 54;
 pub const GET_SERVICE_MANAGER_STATUS_FAILED: u8 = 55;
 pub const SERVICE_MANAGER_FAILED: u8 = 56;
 
-fn run(context: &Context, _: &clap::ArgMatches) -> u8 {
-    // Load config.
+const GIT_EXIT_CODES: GitExitCodes = GitExitCodes {
+    clone_failed: GIT_CLONE_FAILED,
+    open_failed: GIT_OPEN_FAILED,
+    pull_failed: GIT_PULL_FAILED,
+};
+
+fn run(context: &Context, args: &clap::ArgMatches) -> u8 {
+    // Validate config.
     let path = context.project().services();
-    let config: ApplicationConfiguration = match yaml::load_file(&path) {
+
+    match validate_configuration(&path) {
+        Ok(true) => {}
+        Ok(false) => return INVALID_CONFIGURATION,
+        Err(e) => {
+            eprintln!("Failed to open {}: {}", path.display(), e);
+            return OPEN_CONFIGURATION_FAILED;
+        }
+    }
+
+    // Load config.
+    let config = match ApplicationConfiguration::load(&path) {
         Ok(r) => r,
         Err(e) => {
-            return match e {
-                yaml::FileError::OpenFailed(e) => {
-                    eprintln!("Failed to open {}: {}", path.display(), e);
-                    OPEN_CONFIGURATION_FAILED
-                }
-                yaml::FileError::ParseFailed(e) => {
-                    eprintln!("Failed to read {}: {}", path.display(), e);
-                    READ_CONFIGURATION_FAILED
-                }
-            }
+            eprintln!("Failed to read {}: {}", path.display(), e);
+            return READ_CONFIGURATION_FAILED;
         }
     };
 
+    // Select configurations to start.
+    let profiles: Vec<&str> = match args.get_many::<String>("profile") {
+        Some(v) => v.map(|p| p.as_str()).collect(),
+        None => Vec::new(),
+    };
+
+    let declared = config.profiles();
+
+    for profile in &profiles {
+        if !declared.contains(profile) {
+            eprintln!("No instance declares profile '{}'", profile);
+            return UNKNOWN_PROFILE;
+        }
+    }
+
+    let required = config.required_configurations(&profiles);
+
+    // Load lockfile.
+    let lock_path = context.project().services_lock();
+    let lock = match Lockfile::load(&lock_path) {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("Failed to read {}: {}", lock_path.display(), e);
+            return READ_LOCK_FAILED;
+        }
+    };
+
+    // Keep the locked modules since the service definitions are not always read.
+    let mut locked = Lockfile {
+        modules: lock.modules.clone(),
+        ..Default::default()
+    };
+
+    // Fail fast if some configurations cannot be downloaded.
+    if context.offline() {
+        let names = unavailable_offline(
+            context,
+            config
+                .configurations
+                .iter()
+                .filter(|(n, _)| required.contains(n.as_str())),
+            Some(&lock),
+        );
+
+        if !names.is_empty() {
+            eprintln!(
+                "The following configurations are not available in offline mode: {}",
+                names.join(", ")
+            );
+            return OFFLINE_UNAVAILABLE;
+        }
+    }
+
+    // Install modules required by the project.
+    if !require_modules(
+        context,
+        &config.modules,
+        &context.project().path(),
+        Some(&lock),
+        &mut locked,
+        false,
+    ) {
+        return REQUIRE_MODULE_FAILED;
+    }
+
     // Download and build repositories.
     let mut services: HashMap<&str, PlatformConfigurations> = HashMap::new();
 
     for (name, config) in &config.configurations {
+        // Keep the locked revision of the configuration that is not required so it is not lost.
+        if !required.contains(name.as_str()) {
+            if let Some(entry) = lock.configurations.get(name) {
+                locked.configurations.insert(name.clone(), entry.clone());
+            }
+
+            continue;
+        }
+
         let repo = context
             .project()
             .runtime(false)
@@ -65,55 +164,53 @@ fn run(context: &Context, _: &clap::ArgMatches) -> u8 {
         let path = repo.path();
         let service_definition = repo.service_definition();
         let state = repo.build_state(false).unwrap();
+        let revision = lock.revision(name, &config.repository);
 
         // Download.
         let build: bool = if !path.exists() {
             println!("Downloading {} to {}...", name, path.display());
 
-            if let Err(e) = service::repository::download(&config.repository, &path) {
-                return match e {
-                    service::repository::DownloadError::InvalidOption(name) => {
-                        eprintln!("Invalid value for repository option '{}'", name);
-                        INVALID_REPOSITORY_OPTION
-                    }
-                    service::repository::DownloadError::GitCloneFailed(e) => {
-                        eprintln!("Failed to clone the repository: {}", e);
-                        GIT_CLONE_FAILED
-                    }
-                };
+            let mut progress = ProgressRenderer::new();
+            let result =
+                service::repository::download(context, &config.repository, &path, revision, |p| {
+                    progress.update(p)
+                });
+
+            progress.finish();
+
+            if let Err(e) = result {
+                return repository_failed(name, &path, e.into(), &GIT_EXIT_CODES);
             }
 
             true
-        } else if !state.built_time().path().exists() {
-            println!("Updating {}...", name);
-
-            if let Err(e) = service::repository::update(&config.repository, &path) {
-                return match e {
-                    service::repository::UpdateError::InvalidOption(name) => {
-                        eprintln!("Invalid value for repository option '{}'", name);
-                        INVALID_REPOSITORY_OPTION
-                    }
-                    service::repository::UpdateError::GitOpenFailed(e) => {
-                        eprintln!(
-                            "Failed to open {} as a Git repository: {}",
-                            path.display(),
-                            e
-                        );
-                        GIT_OPEN_FAILED
-                    }
-                    service::repository::UpdateError::GitFindOriginFailed(e) => {
-                        eprintln!(
-                            "Failed to find 'origin' remote on repository {}: {}",
-                            path.display(),
-                            e
-                        );
-                        GIT_PULL_FAILED
-                    }
-                    service::repository::UpdateError::GitFetchOriginFailed(e) => {
-                        eprintln!("Failed to pull {}: {}", path.display(), e);
-                        GIT_PULL_FAILED
-                    }
-                };
+        } else if !state.built_time().path().exists()
+            || (revision.is_some()
+                && service::repository::revision(&config.repository, &path).as_deref() != revision)
+        {
+            let mut progress = ProgressRenderer::new();
+            let result = match revision {
+                Some(revision) => {
+                    println!("Checking out {} to {}...", name, revision);
+                    service::repository::checkout(
+                        context,
+                        &config.repository,
+                        &path,
+                        revision,
+                        |p| progress.update(p),
+                    )
+                }
+                None => {
+                    println!("Updating {}...", name);
+                    service::repository::update(context, &config.repository, &path, |p| {
+                        progress.update(p)
+                    })
+                }
+            };
+
+            progress.finish();
+
+            if let Err(e) = result {
+                return repository_failed(name, &path, e.into(), &GIT_EXIT_CODES);
             }
 
             true
@@ -121,23 +218,34 @@ fn run(context: &Context, _: &clap::ArgMatches) -> u8 {
             false
         };
 
+        // Record the revision.
+        if let Some(revision) = service::repository::revision(&config.repository, &path) {
+            let entry = LockedConfiguration {
+                uri: config.repository.uri.to_string(),
+                revision,
+            };
+
+            locked.configurations.insert(name.clone(), entry);
+        }
+
         // Read service definition.
         let service: ServiceDefinition = match yaml::load_file(&service_definition) {
             Ok(r) => r,
-            Err(e) => {
-                return match e {
-                    yaml::FileError::OpenFailed(e) => {
-                        eprintln!("Failed to open {}: {}", service_definition.display(), e);
-                        OPEN_DEFINITION_FAILED
-                    }
-                    yaml::FileError::ParseFailed(e) => {
-                        eprintln!("Failed to read {}: {}", service_definition.display(), e);
-                        READ_DEFINITION_FAILED
-                    }
-                }
-            }
+            Err(e) => return definition_failed(&service_definition, e),
         };
 
+        // Install modules required by the service.
+        if !require_modules(
+            context,
+            &service.modules,
+            &path,
+            Some(&lock),
+            &mut locked,
+            false,
+        ) {
+            return REQUIRE_MODULE_FAILED;
+        }
+
         let (config, platform) = match service.flatten() {
             Some(v) => v,
             None => {
@@ -179,18 +287,27 @@ fn run(context: &Context, _: &clap::ArgMatches) -> u8 {
         }
     }
 
+    // Write lockfile.
+    if !context.offline() && locked != lock {
+        if let Err(e) = locked.save(&lock_path) {
+            eprintln!("Failed to write {}: {}", lock_path.display(), e);
+            return WRITE_LOCK_FAILED;
+        }
+    }
+
     // Start Service Manager.
-    if let Some(exit) = start_service_manager() {
+    if let Some(exit) = start_service_manager(context) {
         return exit;
     }
 
     SUCCESS
 }
 
-fn start_service_manager() -> Option<u8> {
+fn start_service_manager(context: &Context) -> Option<u8> {
     // Launch Service Manager.
     let process = std::process::Command::new(current_exe().unwrap())
         .env("LOCENV_PROCESS_MODE", "service-manager")
+        .current_dir(context.project().path())
         .stdin(Stdio::null())
         .stdout(Stdio::piped())
         .spawn()
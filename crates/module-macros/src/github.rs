@@ -13,6 +13,7 @@ pub fn derive_headers(input: DeriveInput) -> syn::Result<TokenStream> {
                     content_length: None,
                     user_agent: Some("locenv"),
                     accept: None,
+                    authorization: None,
                 }
             }
         }
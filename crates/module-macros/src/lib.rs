@@ -3,7 +3,8 @@ use syn::{parse_macro_input, DeriveInput, Error};
 
 mod github;
 
-/// GitHub required User-Agent to be set otherwise we will get 403.
+/// GitHub required User-Agent to be set otherwise we will get 403. The endpoint need to set
+/// `authorization` in `override_request_headers` to authenticate the request.
 #[proc_macro_derive(GitHubHeaders)]
 pub fn derive_github_headers(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
@@ -1,13 +1,42 @@
-use serde::Deserialize;
+use crate::PackageId;
+use schemars::JsonSchema;
+use semver::Version;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer};
+use std::collections::BTreeMap;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, JsonSchema)]
 pub struct ModuleDefinition {
     pub name: String,
-    pub version: u32,
+
+    /// Semantic version of the module (e.g. `1.2.3`).
+    #[serde(deserialize_with = "deserialize_version")]
+    #[schemars(with = "String")]
+    pub version: Version,
     pub program: Program,
+
+    /// Other modules that required by this module, keyed by the name of the module.
+    #[serde(default)]
+    pub dependencies: BTreeMap<String, PackageId>,
+}
+
+/// Deserialize a semantic version. A bare integer is accepted as the major version for the modules that
+/// was published before semantic version is required.
+fn deserialize_version<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Version, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Major(u64),
+        Full(String),
+    }
+
+    match Raw::deserialize(deserializer)? {
+        Raw::Major(v) => Ok(Version::new(v, 0, 0)),
+        Raw::Full(v) => v.parse().map_err(D::Error::custom),
+    }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, JsonSchema)]
 #[serde(untagged)]
 pub enum Program {
     Script(String),
@@ -15,7 +44,7 @@ pub enum Program {
 }
 
 /// Represents native programs for each platform.
-#[derive(Deserialize)]
+#[derive(Deserialize, JsonSchema)]
 pub struct BinaryProgram {
     pub linux: Option<BinaryFiles>,
     pub darwin: Option<BinaryFiles>,
@@ -40,7 +69,7 @@ impl BinaryProgram {
 }
 
 /// Represents native programs for each CPU type.
-#[derive(Deserialize)]
+#[derive(Deserialize, JsonSchema)]
 pub struct BinaryFiles {
     pub aarch64: Option<String>,
     pub amd64: Option<String>,
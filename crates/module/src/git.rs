@@ -0,0 +1,143 @@
+use crate::package::{Package, PackageContent};
+use crate::registry::{GetPackageError, PackageRegistry};
+use crate::release::keep_highest;
+use git2::build::{CheckoutBuilder, RepoBuilder};
+use git2::{Cred, FetchOptions, RemoteCallbacks, Repository};
+use semver::{Version, VersionReq};
+use std::fmt::{Display, Formatter};
+
+/// Registry for the Git repositories (e.g. `git:https://example.com/mod.git`).
+pub struct Git;
+
+impl PackageRegistry for Git {
+    fn get_package(
+        &self,
+        name: &str,
+        version: Option<&VersionReq>,
+    ) -> Result<Package, GetPackageError> {
+        get_package(name, version)
+    }
+}
+
+/// Clone the repository in `id` (e.g. `https://example.com/mod.git#v1.0.0`) and use its content as a
+/// package. Without the reference, the tag with the highest version that match `version` is used, or
+/// the default branch if `version` is [`None`].
+fn get_package(id: &str, version: Option<&VersionReq>) -> Result<Package, GetPackageError> {
+    // Parse ID.
+    let (url, reference) = match id.split_once('#') {
+        Some((u, r)) => (u, Some(r)),
+        None => (id, None),
+    };
+
+    if url.is_empty() || reference == Some("") {
+        return Err(GetPackageError::InvalidIdentifier);
+    }
+
+    // Clone.
+    let content = tempfile::tempdir().unwrap();
+    let mut callbacks = RemoteCallbacks::new();
+
+    if !url.contains("://") {
+        callbacks.credentials(|_, username, _| {
+            let mut private = dirs::home_dir().unwrap();
+
+            private.push(".ssh");
+            private.push("id_rsa");
+
+            Cred::ssh_key(username.unwrap(), None, &private, None)
+        });
+    }
+
+    let mut fetch = FetchOptions::new();
+
+    fetch.remote_callbacks(callbacks);
+
+    let repo = RepoBuilder::new()
+        .fetch_options(fetch)
+        .clone(url, content.path())
+        .map_err(Error::CloneFailed)?;
+
+    // Checkout.
+    let revision = match (reference, version) {
+        (Some(r), _) => r.to_owned(),
+        (None, Some(v)) => find_tag(&repo, v)?,
+        (None, None) => "HEAD".into(),
+    };
+
+    checkout(&repo, &revision)?;
+    drop(repo);
+
+    // Remove the repository data so it will not be installed.
+    std::fs::remove_dir_all(content.path().join(".git")).map_err(Error::CleanupFailed)?;
+
+    Ok(Package {
+        content: PackageContent::Directory(content),
+        checksum: None,
+        signature: None,
+    })
+}
+
+fn checkout(repo: &Repository, revision: &str) -> Result<(), Error> {
+    let object = match repo.revparse_single(revision) {
+        Ok(r) => r,
+        Err(_) => match repo.revparse_single(&format!("origin/{}", revision)) {
+            Ok(r) => r,
+            Err(_) => return Err(Error::ReferenceNotFound(revision.into())),
+        },
+    };
+
+    repo.checkout_tree(&object, Some(CheckoutBuilder::new().force()))
+        .map_err(Error::CheckoutFailed)
+}
+
+/// Find the tag with the highest version that match `version`. The version is taken from the tag name
+/// with an optional `v` prefix.
+fn find_tag(repo: &Repository, version: &VersionReq) -> Result<String, GetPackageError> {
+    let tags = repo.tag_names(None).map_err(Error::CloneFailed)?;
+    let mut found: Option<(Version, &str)> = None;
+
+    for tag in tags.iter().flatten() {
+        keep_highest(&mut found, version, tag, tag);
+    }
+
+    match found {
+        Some((_, t)) => Ok(format!("refs/tags/{}", t)),
+        None => Err(GetPackageError::NoMatchingRelease(version.clone())),
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    CloneFailed(git2::Error),
+    ReferenceNotFound(String),
+    CheckoutFailed(git2::Error),
+    CleanupFailed(std::io::Error),
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::CloneFailed(e) => Some(e),
+            Self::CheckoutFailed(e) => Some(e),
+            Self::CleanupFailed(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Self::CloneFailed(e) => write!(f, "clone failed: {}", e),
+            Self::ReferenceNotFound(r) => write!(f, "reference {} does not exist", r),
+            Self::CheckoutFailed(e) => write!(f, "checkout failed: {}", e),
+            Self::CleanupFailed(e) => write!(f, "remove repository data failed: {}", e),
+        }
+    }
+}
+
+impl From<Error> for GetPackageError {
+    fn from(e: Error) -> Self {
+        Self::Failed(Box::new(e))
+    }
+}
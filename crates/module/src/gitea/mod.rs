@@ -0,0 +1,142 @@
+use self::api::repos::{
+    DownloadReleaseAsset, DownloadReleaseAssetError, ListReleases, ListReleasesError, Release,
+    ReleaseAsset,
+};
+use crate::package::Package;
+use crate::registry::{GetPackageError, PackageRegistry};
+use crate::release::ReleaseHost;
+use semver::VersionReq;
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+
+mod api;
+
+/// URL of the Gitea instance to use when `LOCENV_GITEA_URL` is not set.
+const DEFAULT_URL: &str = "https://gitea.com";
+
+/// Registry for the releases on Gitea (e.g. `gitea:owner/repo`).
+pub struct Gitea {
+    url: String,
+    authorization: Option<String>,
+}
+
+impl Gitea {
+    /// Create a registry for the instance in `LOCENV_GITEA_URL` (or https://gitea.com) with the token
+    /// in `GITEA_TOKEN`.
+    pub fn from_env() -> Self {
+        let url = std::env::var("LOCENV_GITEA_URL").unwrap_or_else(|_| DEFAULT_URL.into());
+
+        Self {
+            url: url.trim_end_matches('/').into(),
+            authorization: std::env::var("GITEA_TOKEN")
+                .ok()
+                .map(|t| format!("token {}", t)),
+        }
+    }
+}
+
+impl PackageRegistry for Gitea {
+    /// Download the package from the release of the repository `name` (e.g. `owner/repo`).
+    fn get_package(
+        &self,
+        name: &str,
+        version: Option<&VersionReq>,
+    ) -> Result<Package, GetPackageError> {
+        // Parse ID.
+        let (owner, repo) = match name.split_once('/') {
+            Some((o, r)) if !o.is_empty() && !r.is_empty() && !r.contains('/') => (o, r),
+            _ => return Err(GetPackageError::InvalidIdentifier),
+        };
+
+        let repository = Repository {
+            gitea: self,
+            owner,
+            repo,
+        };
+
+        crate::release::get_package(&repository, version)
+    }
+}
+
+/// Represents the releases of a repository on the Gitea instance.
+struct Repository<'a> {
+    gitea: &'a Gitea,
+    owner: &'a str,
+    repo: &'a str,
+}
+
+impl<'a> ReleaseHost for Repository<'a> {
+    type Release = Release;
+    type Asset = ReleaseAsset;
+    type Error = Error;
+
+    const PER_PAGE: usize = ListReleases::PER_PAGE;
+
+    fn list_releases(&self, page: u32) -> Result<Vec<Release>, Error> {
+        let gitea = self.gitea;
+        let authorization = gitea.authorization.as_deref();
+        let releases = ListReleases::new(&gitea.url, self.owner, self.repo, page, authorization);
+
+        kuro::execute(releases).map_err(Error::ListReleasesFailed)
+    }
+
+    /// Download `asset` of the release. The token is sent only to the Gitea instance.
+    fn download(&self, asset: &ReleaseAsset) -> Result<File, Error> {
+        let gitea = self.gitea;
+        let url = &asset.browser_download_url;
+        let authorization = if url.starts_with(&format!("{}/", gitea.url)) {
+            gitea.authorization.as_deref()
+        } else {
+            None
+        };
+
+        kuro::execute(DownloadReleaseAsset::new(url, authorization))
+            .map_err(Error::DownloadReleaseFailed)
+    }
+
+    fn tag_name(release: &Release) -> &str {
+        &release.tag_name
+    }
+
+    fn is_published(release: &Release) -> bool {
+        !release.draft
+    }
+
+    fn assets(release: &Release) -> &[ReleaseAsset] {
+        &release.assets
+    }
+
+    fn asset_name(asset: &ReleaseAsset) -> &str {
+        &asset.name
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    ListReleasesFailed(ListReleasesError),
+    DownloadReleaseFailed(DownloadReleaseAssetError),
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::ListReleasesFailed(e) => Some(e),
+            Self::DownloadReleaseFailed(e) => Some(e),
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Self::ListReleasesFailed(e) => write!(f, "list releases failed: {}", e),
+            Self::DownloadReleaseFailed(e) => write!(f, "download release failed: {}", e),
+        }
+    }
+}
+
+impl From<Error> for GetPackageError {
+    fn from(e: Error) -> Self {
+        Self::Failed(Box::new(e))
+    }
+}
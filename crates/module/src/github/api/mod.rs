@@ -1 +1,112 @@
+use http::header::HeaderName;
+use std::fmt::{Display, Formatter};
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
 pub mod repos;
+
+/// Gets the value of `Authorization` header from `GITHUB_TOKEN` or `GH_TOKEN`.
+pub fn authorization() -> Option<&'static str> {
+    static VALUE: OnceLock<Option<String>> = OnceLock::new();
+
+    VALUE
+        .get_or_init(|| {
+            std::env::var("GITHUB_TOKEN")
+                .or_else(|_| std::env::var("GH_TOKEN"))
+                .ok()
+                .filter(|t| !t.is_empty())
+                .map(|t| format!("Bearer {}", t))
+        })
+        .as_deref()
+}
+
+/// Status of the rate limit that reported by GitHub.
+///
+/// See https://docs.github.com/en/rest/overview/resources-in-the-rest-api#rate-limiting for more
+/// information.
+#[derive(Debug)]
+pub struct RateLimit {
+    pub limit: Option<u64>,
+    pub remaining: Option<u64>,
+    pub reset: Option<u64>,
+    pub resource: Option<String>,
+    pub retry_after: Option<u64>,
+    pub authenticated: bool,
+}
+
+impl RateLimit {
+    pub fn new() -> Self {
+        Self {
+            limit: None,
+            remaining: None,
+            reset: None,
+            resource: None,
+            retry_after: None,
+            authenticated: authorization().is_some(),
+        }
+    }
+
+    /// Update the status from the response header. Other headers are ignored.
+    pub fn process_header(&mut self, name: &HeaderName, value: &str) {
+        match name.as_str() {
+            "x-ratelimit-limit" => self.limit = value.parse().ok(),
+            "x-ratelimit-remaining" => self.remaining = value.parse().ok(),
+            "x-ratelimit-reset" => self.reset = value.parse().ok(),
+            "x-ratelimit-resource" => self.resource = Some(value.into()),
+            "retry-after" => self.retry_after = value.parse().ok(),
+            _ => {}
+        }
+    }
+
+    /// Check if the request was rejected because of the rate limit.
+    pub fn exceeded(&self) -> bool {
+        self.remaining == Some(0) || self.retry_after.is_some()
+    }
+
+    /// Gets the number of seconds until the request can be made again.
+    fn wait(&self) -> Option<u64> {
+        if let Some(v) = self.retry_after {
+            return Some(v);
+        }
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+
+        self.reset.map(|r| r.saturating_sub(now.as_secs()))
+    }
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Display for RateLimit {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        if self.remaining == Some(0) {
+            f.write_str("API rate limit")?;
+
+            if let Some(v) = self.limit {
+                write!(f, " of {} requests", v)?;
+            }
+
+            if let Some(v) = &self.resource {
+                write!(f, " for {}", v)?;
+            }
+
+            f.write_str(" exceeded")?;
+        } else {
+            f.write_str("secondary API rate limit exceeded")?;
+        }
+
+        if let Some(v) = self.wait() {
+            write!(f, ", try again in {} seconds", v)?;
+        }
+
+        if !self.authenticated {
+            f.write_str(" (set GITHUB_TOKEN to use the higher limit of authenticated requests)")?;
+        }
+
+        Ok(())
+    }
+}
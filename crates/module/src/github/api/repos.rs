@@ -1,3 +1,5 @@
+use super::RateLimit;
+use http::header::HeaderName;
 use http::{Method, StatusCode};
 use kuro::mime::{MediaType, APPLICATION_JSON};
 use kuro::{Endpoint, Headers, StatusLine};
@@ -19,6 +21,8 @@ use std::io::Write;
 pub struct GetLatestRelease<'owner, 'repo> {
     owner: &'owner str,
     repo: &'repo str,
+    throttled: Option<StatusCode>,
+    rate_limit: RateLimit,
     response: Vec<u8>,
 }
 
@@ -27,6 +31,8 @@ impl<'owner, 'repo> GetLatestRelease<'owner, 'repo> {
         Self {
             owner,
             repo,
+            throttled: None,
+            rate_limit: RateLimit::new(),
             response: Vec::new(),
         }
     }
@@ -46,21 +52,42 @@ impl<'owner, 'repo> Endpoint for GetLatestRelease<'owner, 'repo> {
         format!("https://api.github.com/repos/{}/{}/releases/latest", o, r).into()
     }
 
+    fn override_request_headers<'a>(&'a self, h: &mut Headers<'a>) {
+        h.authorization = super::authorization();
+    }
+
     fn process_response_status(&mut self, line: &StatusLine) -> Result<(), Self::Err> {
         let c = line.code();
 
-        if c == StatusCode::OK {
-            Ok(())
-        } else {
-            Err(GetLatestReleaseError::UnexpectedStatusCode(c))
+        match c {
+            StatusCode::OK => Ok(()),
+            StatusCode::FORBIDDEN | StatusCode::TOO_MANY_REQUESTS => {
+                // We need the headers to check if the request was throttled.
+                self.throttled = Some(c);
+                Ok(())
+            }
+            _ => Err(GetLatestReleaseError::UnexpectedStatusCode(c)),
         }
     }
 
+    fn process_response_header(&mut self, name: &HeaderName, value: &str) -> Result<(), Self::Err> {
+        self.rate_limit.process_header(name, value);
+        Ok(())
+    }
+
     fn begin_response_body(
         &mut self,
         ty: Option<&MediaType>,
         _: Option<u64>,
     ) -> Result<(), Self::Err> {
+        if let Some(c) = self.throttled {
+            return Err(if self.rate_limit.exceeded() {
+                GetLatestReleaseError::RateLimitExceeded(std::mem::take(&mut self.rate_limit))
+            } else {
+                GetLatestReleaseError::UnexpectedStatusCode(c)
+            });
+        }
+
         if let Some(t) = ty {
             if t == &APPLICATION_JSON {
                 Ok(())
@@ -91,13 +118,166 @@ impl<'owner, 'repo> Endpoint for GetLatestRelease<'owner, 'repo> {
     }
 }
 
+/// List releases of the repository, newest first.
+///
+/// See https://docs.github.com/en/rest/releases/releases#list-releases for more information.
+#[derive(GitHubHeaders, FollowLocation)]
+#[kuro(error = "ListReleasesError")]
+pub struct ListReleases<'owner, 'repo> {
+    owner: &'owner str,
+    repo: &'repo str,
+    page: u32,
+    throttled: Option<StatusCode>,
+    rate_limit: RateLimit,
+    response: Vec<u8>,
+}
+
+impl<'owner, 'repo> ListReleases<'owner, 'repo> {
+    /// Maximum number of releases per page.
+    pub const PER_PAGE: usize = 100;
+
+    pub fn new(owner: &'owner str, repo: &'repo str, page: u32) -> Self {
+        Self {
+            owner,
+            repo,
+            page,
+            throttled: None,
+            rate_limit: RateLimit::new(),
+            response: Vec::new(),
+        }
+    }
+}
+
+impl<'owner, 'repo> Endpoint for ListReleases<'owner, 'repo> {
+    type Output = Vec<Release>;
+
+    fn method(&self) -> &Method {
+        &Method::GET
+    }
+
+    fn url(&self) -> Cow<'_, str> {
+        let o = self.owner;
+        let r = self.repo;
+        let n = Self::PER_PAGE;
+        let p = self.page;
+
+        format!(
+            "https://api.github.com/repos/{}/{}/releases?per_page={}&page={}",
+            o, r, n, p
+        )
+        .into()
+    }
+
+    fn override_request_headers<'a>(&'a self, h: &mut Headers<'a>) {
+        h.authorization = super::authorization();
+    }
+
+    fn process_response_status(&mut self, line: &StatusLine) -> Result<(), Self::Err> {
+        let c = line.code();
+
+        match c {
+            StatusCode::OK => Ok(()),
+            StatusCode::FORBIDDEN | StatusCode::TOO_MANY_REQUESTS => {
+                // We need the headers to check if the request was throttled.
+                self.throttled = Some(c);
+                Ok(())
+            }
+            _ => Err(ListReleasesError::UnexpectedStatusCode(c)),
+        }
+    }
+
+    fn process_response_header(&mut self, name: &HeaderName, value: &str) -> Result<(), Self::Err> {
+        self.rate_limit.process_header(name, value);
+        Ok(())
+    }
+
+    fn begin_response_body(
+        &mut self,
+        ty: Option<&MediaType>,
+        _: Option<u64>,
+    ) -> Result<(), Self::Err> {
+        if let Some(c) = self.throttled {
+            return Err(if self.rate_limit.exceeded() {
+                ListReleasesError::RateLimitExceeded(std::mem::take(&mut self.rate_limit))
+            } else {
+                ListReleasesError::UnexpectedStatusCode(c)
+            });
+        }
+
+        if let Some(t) = ty {
+            if t == &APPLICATION_JSON {
+                Ok(())
+            } else {
+                Err(ListReleasesError::InvalidContentType(t.to_owned()))
+            }
+        } else {
+            Ok(())
+        }
+    }
+
+    fn process_response_body(&mut self, chunk: &[u8]) -> Result<(), Self::Err> {
+        self.response.extend_from_slice(chunk);
+        Ok(())
+    }
+
+    fn new_invalid_response_header(&self, line: &[u8]) -> Self::Err {
+        ListReleasesError::InvalidResponseHeader(line.into())
+    }
+
+    fn new_http_stack_error(&self, cause: curl::Error) -> Self::Err {
+        ListReleasesError::HttpStackFailed(cause)
+    }
+
+    fn build_output(self, _: StatusLine) -> Result<Self::Output, Self::Err> {
+        serde_json::from_slice(&self.response)
+            .map_err(|_| ListReleasesError::InvalidContent(self.response))
+    }
+}
+
+#[derive(Debug)]
+pub enum ListReleasesError {
+    HttpStackFailed(curl::Error),
+    InvalidResponseHeader(Vec<u8>),
+    UnexpectedStatusCode(StatusCode),
+    RateLimitExceeded(RateLimit),
+    InvalidContentType(MediaType<'static>),
+    InvalidContent(Vec<u8>),
+}
+
+impl Error for ListReleasesError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::HttpStackFailed(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl Display for ListReleasesError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Self::HttpStackFailed(_) => f.write_str("HTTP stack failed"),
+            Self::InvalidResponseHeader(h) => write!(f, "{:?} is not a valid header", h),
+            Self::UnexpectedStatusCode(c) => write!(f, "unexpected status {}", c),
+            Self::RateLimitExceeded(r) => r.fmt(f),
+            Self::InvalidContentType(t) => write!(f, "unexpected content type {}", t),
+            Self::InvalidContent(c) => write!(f, "unexpected content {:?}", c),
+        }
+    }
+}
+
 #[derive(Deserialize)]
 pub struct Release {
+    pub tag_name: String,
     pub assets: Vec<ReleaseAsset>,
+
+    #[serde(default)]
+    pub draft: bool,
 }
 
 #[derive(Deserialize)]
 pub struct ReleaseAsset {
+    pub name: String,
     pub url: String,
 }
 
@@ -106,6 +286,7 @@ pub enum GetLatestReleaseError {
     HttpStackFailed(curl::Error),
     InvalidResponseHeader(Vec<u8>),
     UnexpectedStatusCode(StatusCode),
+    RateLimitExceeded(RateLimit),
     InvalidContentType(MediaType<'static>),
     InvalidContent(Vec<u8>),
 }
@@ -125,6 +306,7 @@ impl Display for GetLatestReleaseError {
             Self::HttpStackFailed(_) => f.write_str("HTTP stack failed"),
             Self::InvalidResponseHeader(h) => write!(f, "{:?} is not a valid header", h),
             Self::UnexpectedStatusCode(c) => write!(f, "unexpected status {}", c),
+            Self::RateLimitExceeded(r) => r.fmt(f),
             Self::InvalidContentType(t) => write!(f, "unexpected content type {}", t),
             Self::InvalidContent(c) => write!(f, "unexpected content {:?}", c),
         }
@@ -163,6 +345,7 @@ impl<'url> Endpoint for DownloadReleaseAsset<'url> {
 
     fn override_request_headers<'a>(&'a self, h: &mut Headers<'a>) {
         h.accept = Some("application/octet-stream");
+        h.authorization = super::authorization();
     }
 
     fn process_response_status(&mut self, line: &StatusLine) -> Result<(), Self::Err> {
@@ -1,13 +1,31 @@
 use self::api::repos::{
     DownloadReleaseAsset, DownloadReleaseAssetError, GetLatestRelease, GetLatestReleaseError,
+    ListReleases, ListReleasesError, Release, ReleaseAsset,
 };
+use crate::package::Package;
+use crate::registry::{GetPackageError, PackageRegistry};
+use crate::release::ReleaseHost;
+use semver::VersionReq;
 use std::fmt::{Display, Formatter};
 use std::fs::File;
-use std::io::Seek;
 
 mod api;
 
-pub fn get_latest_package(id: &str) -> Result<File, Error> {
+/// Registry for the releases on GitHub (e.g. `github:locenv/mod-autoconf`).
+pub struct GitHub;
+
+impl PackageRegistry for GitHub {
+    fn get_package(
+        &self,
+        name: &str,
+        version: Option<&VersionReq>,
+    ) -> Result<Package, GetPackageError> {
+        get_package(name, version)
+    }
+}
+
+/// Parse the repository from `id` (e.g. `locenv/mod-autoconf`) and download its package.
+fn get_package(id: &str, version: Option<&VersionReq>) -> Result<Package, GetPackageError> {
     // Parse ID.
     let mut buffer = String::with_capacity(id.len());
     let mut owner: Option<String> = None;
@@ -16,13 +34,13 @@ pub fn get_latest_package(id: &str) -> Result<File, Error> {
         if c == '/' {
             if owner.is_none() {
                 if buffer.is_empty() {
-                    return Err(Error::InvalidIdentifier);
+                    return Err(GetPackageError::InvalidIdentifier);
                 }
 
                 owner = Some(buffer.clone());
                 buffer.truncate(0);
             } else {
-                return Err(Error::InvalidIdentifier);
+                return Err(GetPackageError::InvalidIdentifier);
             }
         } else {
             buffer.push(c);
@@ -30,34 +48,66 @@ pub fn get_latest_package(id: &str) -> Result<File, Error> {
     }
 
     if owner.is_none() || buffer.is_empty() {
-        return Err(Error::InvalidIdentifier);
+        return Err(GetPackageError::InvalidIdentifier);
     }
 
-    let owner = owner.unwrap();
-    let repo = buffer;
-
-    // Get latest release.
-    let release = match kuro::execute(GetLatestRelease::new(&owner, &repo)) {
-        Ok(r) => r,
-        Err(e) => return Err(Error::GetReleaseFailed(e)),
+    let repository = Repository {
+        owner: owner.unwrap(),
+        repo: buffer,
     };
 
-    // Download release asset.
-    let mut asset = match kuro::execute(DownloadReleaseAsset::new(&release.assets[0].url)) {
-        Ok(r) => r,
-        Err(e) => return Err(Error::DownloadReleaseFailed(e)),
-    };
+    crate::release::get_package(&repository, version)
+}
+
+/// Represents the releases of a repository on GitHub.
+struct Repository {
+    owner: String,
+    repo: String,
+}
+
+impl ReleaseHost for Repository {
+    type Release = Release;
+    type Asset = ReleaseAsset;
+    type Error = Error;
+
+    const PER_PAGE: usize = ListReleases::PER_PAGE;
+
+    fn list_releases(&self, page: u32) -> Result<Vec<Release>, Error> {
+        kuro::execute(ListReleases::new(&self.owner, &self.repo, page))
+            .map_err(Error::ListReleasesFailed)
+    }
 
-    // Reset file position before return.
-    asset.rewind().unwrap();
+    fn download(&self, asset: &ReleaseAsset) -> Result<File, Error> {
+        kuro::execute(DownloadReleaseAsset::new(&asset.url)).map_err(Error::DownloadReleaseFailed)
+    }
+
+    fn tag_name(release: &Release) -> &str {
+        &release.tag_name
+    }
+
+    fn is_published(release: &Release) -> bool {
+        !release.draft
+    }
+
+    fn assets(release: &Release) -> &[ReleaseAsset] {
+        &release.assets
+    }
 
-    Ok(asset)
+    fn asset_name(asset: &ReleaseAsset) -> &str {
+        &asset.name
+    }
+
+    fn latest_release(&self) -> Result<Release, GetPackageError> {
+        kuro::execute(GetLatestRelease::new(&self.owner, &self.repo))
+            .map_err(|e| Error::GetReleaseFailed(e).into())
+    }
 }
 
 #[derive(Debug)]
+#[allow(clippy::enum_variant_names)]
 pub enum Error {
-    InvalidIdentifier,
     GetReleaseFailed(GetLatestReleaseError),
+    ListReleasesFailed(ListReleasesError),
     DownloadReleaseFailed(DownloadReleaseAssetError),
 }
 
@@ -65,8 +115,8 @@ impl std::error::Error for Error {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
             Self::GetReleaseFailed(e) => Some(e),
+            Self::ListReleasesFailed(e) => Some(e),
             Self::DownloadReleaseFailed(e) => Some(e),
-            _ => None,
         }
     }
 }
@@ -74,9 +124,15 @@ impl std::error::Error for Error {
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
         match self {
-            Self::InvalidIdentifier => f.write_str("invalid package identifier"),
-            Self::GetReleaseFailed(_) => f.write_str("get release failed"),
-            Self::DownloadReleaseFailed(_) => f.write_str("download release failed"),
+            Self::GetReleaseFailed(e) => write!(f, "get release failed: {}", e),
+            Self::ListReleasesFailed(e) => write!(f, "list releases failed: {}", e),
+            Self::DownloadReleaseFailed(e) => write!(f, "download release failed: {}", e),
         }
     }
 }
+
+impl From<Error> for GetPackageError {
+    fn from(e: Error) -> Self {
+        Self::Failed(Box::new(e))
+    }
+}
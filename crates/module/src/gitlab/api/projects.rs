@@ -0,0 +1,261 @@
+use http::{Method, StatusCode};
+use kuro::mime::{MediaType, APPLICATION_JSON};
+use kuro::{Endpoint, Headers, StatusLine};
+use kuro_macros::{kuro, FollowLocation, NoDefaultHeaders};
+use serde::Deserialize;
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::Write;
+
+/// List releases of the project, newest first.
+///
+/// See https://docs.gitlab.com/ee/api/releases/#list-releases for more information.
+#[derive(FollowLocation, NoDefaultHeaders)]
+#[kuro(error = "ListReleasesError")]
+pub struct ListReleases<'request> {
+    base: &'request str,
+    project: &'request str,
+    page: u32,
+    authorization: Option<&'request str>,
+    response: Vec<u8>,
+}
+
+impl<'request> ListReleases<'request> {
+    /// Maximum number of releases per page.
+    pub const PER_PAGE: usize = 100;
+
+    /// `base` is the URL of GitLab instance without a trailing slash and `project` is the
+    /// URL-encoded path of the project.
+    pub fn new(
+        base: &'request str,
+        project: &'request str,
+        page: u32,
+        authorization: Option<&'request str>,
+    ) -> Self {
+        Self {
+            base,
+            project,
+            page,
+            authorization,
+            response: Vec::new(),
+        }
+    }
+}
+
+impl<'request> Endpoint for ListReleases<'request> {
+    type Output = Vec<Release>;
+
+    fn method(&self) -> &Method {
+        &Method::GET
+    }
+
+    fn url(&self) -> Cow<'_, str> {
+        let b = self.base;
+        let p = self.project;
+        let n = Self::PER_PAGE;
+        let i = self.page;
+
+        format!(
+            "{}/api/v4/projects/{}/releases?per_page={}&page={}",
+            b, p, n, i
+        )
+        .into()
+    }
+
+    fn override_request_headers<'a>(&'a self, h: &mut Headers<'a>) {
+        h.user_agent = Some("locenv");
+        h.authorization = self.authorization;
+    }
+
+    fn process_response_status(&mut self, line: &StatusLine) -> Result<(), Self::Err> {
+        let c = line.code();
+
+        if c == StatusCode::OK {
+            Ok(())
+        } else {
+            Err(ListReleasesError::UnexpectedStatusCode(c))
+        }
+    }
+
+    fn begin_response_body(
+        &mut self,
+        ty: Option<&MediaType>,
+        _: Option<u64>,
+    ) -> Result<(), Self::Err> {
+        if let Some(t) = ty {
+            if t == &APPLICATION_JSON {
+                Ok(())
+            } else {
+                Err(ListReleasesError::InvalidContentType(t.to_owned()))
+            }
+        } else {
+            Ok(())
+        }
+    }
+
+    fn process_response_body(&mut self, chunk: &[u8]) -> Result<(), Self::Err> {
+        self.response.extend_from_slice(chunk);
+        Ok(())
+    }
+
+    fn new_invalid_response_header(&self, line: &[u8]) -> Self::Err {
+        ListReleasesError::InvalidResponseHeader(line.into())
+    }
+
+    fn new_http_stack_error(&self, cause: curl::Error) -> Self::Err {
+        ListReleasesError::HttpStackFailed(cause)
+    }
+
+    fn build_output(self, _: StatusLine) -> Result<Self::Output, Self::Err> {
+        serde_json::from_slice(&self.response)
+            .map_err(|_| ListReleasesError::InvalidContent(self.response))
+    }
+}
+
+#[derive(Debug)]
+pub enum ListReleasesError {
+    HttpStackFailed(curl::Error),
+    InvalidResponseHeader(Vec<u8>),
+    UnexpectedStatusCode(StatusCode),
+    InvalidContentType(MediaType<'static>),
+    InvalidContent(Vec<u8>),
+}
+
+impl Error for ListReleasesError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::HttpStackFailed(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl Display for ListReleasesError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Self::HttpStackFailed(_) => f.write_str("HTTP stack failed"),
+            Self::InvalidResponseHeader(h) => write!(f, "{:?} is not a valid header", h),
+            Self::UnexpectedStatusCode(c) => write!(f, "unexpected status {}", c),
+            Self::InvalidContentType(t) => write!(f, "unexpected content type {}", t),
+            Self::InvalidContent(c) => write!(f, "unexpected content {:?}", c),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct Release {
+    pub tag_name: String,
+    pub assets: ReleaseAssets,
+
+    #[serde(default)]
+    pub upcoming_release: bool,
+}
+
+#[derive(Deserialize)]
+pub struct ReleaseAssets {
+    pub links: Vec<ReleaseLink>,
+}
+
+#[derive(Deserialize)]
+pub struct ReleaseLink {
+    pub name: String,
+    pub url: String,
+    pub direct_asset_url: Option<String>,
+}
+
+/// Download release asset.
+///
+/// See https://docs.gitlab.com/ee/api/releases/links.html for more information.
+#[derive(FollowLocation, NoDefaultHeaders)]
+#[kuro(error = "DownloadReleaseAssetError")]
+pub struct DownloadReleaseAsset<'request> {
+    url: &'request str,
+    authorization: Option<&'request str>,
+    file: File,
+}
+
+impl<'request> DownloadReleaseAsset<'request> {
+    pub fn new(url: &'request str, authorization: Option<&'request str>) -> Self {
+        Self {
+            url,
+            authorization,
+            file: tempfile::tempfile().unwrap(),
+        }
+    }
+}
+
+impl<'request> Endpoint for DownloadReleaseAsset<'request> {
+    type Output = File;
+
+    fn method(&self) -> &Method {
+        &Method::GET
+    }
+
+    fn url(&self) -> Cow<'_, str> {
+        self.url.into()
+    }
+
+    fn override_request_headers<'a>(&'a self, h: &mut Headers<'a>) {
+        h.user_agent = Some("locenv");
+        h.authorization = self.authorization;
+    }
+
+    fn process_response_status(&mut self, line: &StatusLine) -> Result<(), Self::Err> {
+        let c = line.code();
+
+        if c == StatusCode::OK {
+            Ok(())
+        } else {
+            Err(DownloadReleaseAssetError::UnexpectedStatusCode(c))
+        }
+    }
+
+    fn process_response_body(&mut self, chunk: &[u8]) -> Result<(), Self::Err> {
+        self.file
+            .write_all(chunk)
+            .map_err(DownloadReleaseAssetError::WriteFailed)
+    }
+
+    fn new_invalid_response_header(&self, line: &[u8]) -> Self::Err {
+        DownloadReleaseAssetError::InvalidResponseHeader(line.into())
+    }
+
+    fn new_http_stack_error(&self, cause: curl::Error) -> Self::Err {
+        DownloadReleaseAssetError::HttpStackFailed(cause)
+    }
+
+    fn build_output(self, _: StatusLine) -> Result<Self::Output, Self::Err> {
+        Ok(self.file)
+    }
+}
+
+#[derive(Debug)]
+pub enum DownloadReleaseAssetError {
+    HttpStackFailed(curl::Error),
+    InvalidResponseHeader(Vec<u8>),
+    UnexpectedStatusCode(StatusCode),
+    WriteFailed(std::io::Error),
+}
+
+impl Error for DownloadReleaseAssetError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::HttpStackFailed(e) => Some(e),
+            Self::WriteFailed(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl Display for DownloadReleaseAssetError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Self::HttpStackFailed(_) => f.write_str("HTTP stack failed"),
+            Self::InvalidResponseHeader(h) => write!(f, "{:?} is not a valid header", h),
+            Self::UnexpectedStatusCode(c) => write!(f, "unexpected status {}", c),
+            Self::WriteFailed(_) => f.write_str("write failed"),
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use self::api::projects::{
+    DownloadReleaseAsset, DownloadReleaseAssetError, ListReleases, ListReleasesError, Release,
+    ReleaseLink,
+};
+use crate::package::Package;
+use crate::registry::{GetPackageError, PackageRegistry};
+use crate::release::ReleaseHost;
+use semver::VersionReq;
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+
+mod api;
+
+/// URL of the GitLab instance to use when `LOCENV_GITLAB_URL` is not set.
+const DEFAULT_URL: &str = "https://gitlab.com";
+
+/// Registry for the releases on GitLab (e.g. `gitlab:group/project`).
+pub struct GitLab {
+    url: String,
+    authorization: Option<String>,
+}
+
+impl GitLab {
+    /// Create a registry for the instance in `LOCENV_GITLAB_URL` (or https://gitlab.com) with the
+    /// token in `GITLAB_TOKEN`.
+    pub fn from_env() -> Self {
+        let url = std::env::var("LOCENV_GITLAB_URL").unwrap_or_else(|_| DEFAULT_URL.into());
+
+        Self {
+            url: url.trim_end_matches('/').into(),
+            authorization: std::env::var("GITLAB_TOKEN")
+                .ok()
+                .map(|t| format!("Bearer {}", t)),
+        }
+    }
+}
+
+impl PackageRegistry for GitLab {
+    /// Download the package from the release of the project `name` (e.g. `group/subgroup/project`).
+    fn get_package(
+        &self,
+        name: &str,
+        version: Option<&VersionReq>,
+    ) -> Result<Package, GetPackageError> {
+        // Parse ID.
+        if name.split('/').count() < 2 || name.split('/').any(|p| p.is_empty()) {
+            return Err(GetPackageError::InvalidIdentifier);
+        }
+
+        let project = Project {
+            gitlab: self,
+            id: name.replace('/', "%2F"),
+        };
+
+        crate::release::get_package(&project, version)
+    }
+}
+
+/// Represents the releases of a project on the GitLab instance.
+struct Project<'a> {
+    gitlab: &'a GitLab,
+    id: String,
+}
+
+impl<'a> ReleaseHost for Project<'a> {
+    type Release = Release;
+    type Asset = ReleaseLink;
+    type Error = Error;
+
+    const PER_PAGE: usize = ListReleases::PER_PAGE;
+
+    fn list_releases(&self, page: u32) -> Result<Vec<Release>, Error> {
+        let gitlab = self.gitlab;
+        let authorization = gitlab.authorization.as_deref();
+
+        let releases = ListReleases::new(&gitlab.url, &self.id, page, authorization);
+
+        kuro::execute(releases).map_err(Error::ListReleasesFailed)
+    }
+
+    /// Download `link` of the release. The token is sent only to the GitLab instance.
+    fn download(&self, link: &ReleaseLink) -> Result<File, Error> {
+        let gitlab = self.gitlab;
+        let url = link.direct_asset_url.as_ref().unwrap_or(&link.url);
+        let authorization = if url.starts_with(&format!("{}/", gitlab.url)) {
+            gitlab.authorization.as_deref()
+        } else {
+            None
+        };
+
+        kuro::execute(DownloadReleaseAsset::new(url, authorization))
+            .map_err(Error::DownloadReleaseFailed)
+    }
+
+    fn tag_name(release: &Release) -> &str {
+        &release.tag_name
+    }
+
+    fn is_published(release: &Release) -> bool {
+        !release.upcoming_release
+    }
+
+    fn assets(release: &Release) -> &[ReleaseLink] {
+        &release.assets.links
+    }
+
+    fn asset_name(link: &ReleaseLink) -> &str {
+        &link.name
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    ListReleasesFailed(ListReleasesError),
+    DownloadReleaseFailed(DownloadReleaseAssetError),
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::ListReleasesFailed(e) => Some(e),
+            Self::DownloadReleaseFailed(e) => Some(e),
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Self::ListReleasesFailed(e) => write!(f, "list releases failed: {}", e),
+            Self::DownloadReleaseFailed(e) => write!(f, "download release failed: {}", e),
+        }
+    }
+}
+
+impl From<Error> for GetPackageError {
+    fn from(e: Error) -> Self {
+        Self::Failed(Box::new(e))
+    }
+}
@@ -0,0 +1,182 @@
+use crate::package::{Package, PackageContent};
+use crate::registry::{GetPackageError, PackageRegistry};
+use http::{Method, StatusCode};
+use kuro::{Endpoint, Headers, StatusLine};
+use kuro_macros::{kuro, FollowLocation, NoDefaultHeaders};
+use semver::VersionReq;
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::{Read, Seek, Write};
+
+/// Registry for the packages on a web server (e.g. `https://example.com/mod.zip`). The version
+/// constraint is only used to check the downloaded package.
+pub struct Https;
+
+impl PackageRegistry for Https {
+    fn get_package(&self, name: &str, _: Option<&VersionReq>) -> Result<Package, GetPackageError> {
+        get_package(name)
+    }
+}
+
+/// Download the package from `https:<name>` together with `<url>.sha256` and `<url>.minisig`.
+fn get_package(name: &str) -> Result<Package, GetPackageError> {
+    if !name.starts_with("//") {
+        return Err(GetPackageError::InvalidIdentifier);
+    }
+
+    let url = format!("https:{}", name);
+    let content = download(&url)?;
+    let checksum = download_data(&format!("{}.sha256", url))?;
+    let signature = download_data(&format!("{}.minisig", url))?;
+
+    Ok(Package {
+        content: PackageContent::Archive(content),
+        checksum,
+        signature,
+    })
+}
+
+fn download(url: &str) -> Result<File, Error> {
+    let mut file = kuro::execute(DownloadPackage::new(url)).map_err(Error::DownloadFailed)?;
+
+    // Reset file position before return.
+    file.rewind().unwrap();
+
+    Ok(file)
+}
+
+/// Download `url` into the memory. Returns [`None`] if `url` does not exist. Some servers (e.g. S3)
+/// respond with 403 instead of 404 for the missing file so we treat it the same way.
+fn download_data(url: &str) -> Result<Option<Vec<u8>>, Error> {
+    let mut file = match download(url) {
+        Ok(r) => r,
+        Err(Error::DownloadFailed(DownloadPackageError::UnexpectedStatusCode(
+            StatusCode::NOT_FOUND | StatusCode::FORBIDDEN,
+        ))) => return Ok(None),
+        Err(e) => return Err(e),
+    };
+
+    let mut data = Vec::new();
+
+    file.read_to_end(&mut data).unwrap();
+
+    Ok(Some(data))
+}
+
+/// Download a package over HTTP.
+#[derive(FollowLocation, NoDefaultHeaders)]
+#[kuro(error = "DownloadPackageError")]
+struct DownloadPackage<'url> {
+    url: &'url str,
+    file: File,
+}
+
+impl<'url> DownloadPackage<'url> {
+    fn new(url: &'url str) -> Self {
+        Self {
+            url,
+            file: tempfile::tempfile().unwrap(),
+        }
+    }
+}
+
+impl<'url> Endpoint for DownloadPackage<'url> {
+    type Output = File;
+
+    fn method(&self) -> &Method {
+        &Method::GET
+    }
+
+    fn url(&self) -> Cow<'_, str> {
+        self.url.into()
+    }
+
+    fn override_request_headers<'a>(&'a self, h: &mut Headers<'a>) {
+        h.user_agent = Some("locenv");
+    }
+
+    fn process_response_status(&mut self, line: &StatusLine) -> Result<(), Self::Err> {
+        let c = line.code();
+
+        if c == StatusCode::OK {
+            Ok(())
+        } else {
+            Err(DownloadPackageError::UnexpectedStatusCode(c))
+        }
+    }
+
+    fn process_response_body(&mut self, chunk: &[u8]) -> Result<(), Self::Err> {
+        self.file
+            .write_all(chunk)
+            .map_err(DownloadPackageError::WriteFailed)
+    }
+
+    fn new_invalid_response_header(&self, line: &[u8]) -> Self::Err {
+        DownloadPackageError::InvalidResponseHeader(line.into())
+    }
+
+    fn new_http_stack_error(&self, cause: curl::Error) -> Self::Err {
+        DownloadPackageError::HttpStackFailed(cause)
+    }
+
+    fn build_output(self, _: StatusLine) -> Result<Self::Output, Self::Err> {
+        Ok(self.file)
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    DownloadFailed(DownloadPackageError),
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::DownloadFailed(e) => Some(e),
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Self::DownloadFailed(e) => write!(f, "download failed: {}", e),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum DownloadPackageError {
+    HttpStackFailed(curl::Error),
+    InvalidResponseHeader(Vec<u8>),
+    UnexpectedStatusCode(StatusCode),
+    WriteFailed(std::io::Error),
+}
+
+impl std::error::Error for DownloadPackageError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::HttpStackFailed(e) => Some(e),
+            Self::WriteFailed(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl Display for DownloadPackageError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Self::HttpStackFailed(e) => write!(f, "HTTP stack failed: {}", e),
+            Self::InvalidResponseHeader(h) => write!(f, "{:?} is not a valid header", h),
+            Self::UnexpectedStatusCode(c) => write!(f, "unexpected status {}", c),
+            Self::WriteFailed(e) => write!(f, "write failed: {}", e),
+        }
+    }
+}
+
+impl From<Error> for GetPackageError {
+    fn from(e: Error) -> Self {
+        Self::Failed(Box::new(e))
+    }
+}
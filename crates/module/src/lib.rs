@@ -1,16 +1,19 @@
 pub use self::definition::ModuleDefinition;
 pub use self::package::PackageId;
+pub use self::verification::VerifyError;
+pub use semver::Version;
 
 use self::metadata::MetadataManager;
-use self::package::{InstallationScope, PackageReader, Registry};
+use self::package::{InstallationScope, Package, PackageContent, PackageReader};
+use self::registry::GetPackageError;
 use context::data::ModuleDirectory;
 use context::Context;
 use std::borrow::Cow;
+use std::collections::HashSet;
 use std::error::Error;
 use std::fmt::{Display, Formatter};
 use std::fs::create_dir_all;
 use std::hash::Hash;
-use std::io::{Read, Seek};
 use std::path::Path;
 use std::path::PathBuf;
 use tempfile::TempDir;
@@ -19,10 +22,17 @@ use zip::ZipArchive;
 pub mod definition;
 pub mod metadata;
 
+mod git;
+mod gitea;
 mod github;
+mod gitlab;
+mod https;
+mod local;
 mod package;
+mod registry;
+mod release;
+mod verification;
 
-#[allow(dead_code)]
 pub struct Module<'context, 'name> {
     definition: ModuleDefinition,
     path: PathBuf,
@@ -58,43 +68,78 @@ impl<'context, 'name> Module<'context, 'name> {
     }
 
     pub fn install(context: &'context Context, id: &PackageId) -> Result<Self, InstallError> {
+        Self::install_with(context, id, None, &mut Vec::new())
+    }
+
+    /// Install the module from `id` and its dependencies. The package with `locked` version will be
+    /// installed instead of the latest one if it is specified, but `id` is still recorded as the source
+    /// of the module. `stack` is the names of the modules that their dependencies is being installed.
+    fn install_with(
+        context: &'context Context,
+        id: &PackageId,
+        locked: Option<&Version>,
+        stack: &mut Vec<String>,
+    ) -> Result<Self, InstallError> {
+        if context.offline() {
+            return Err(InstallError::Offline);
+        }
+
         // Download module package.
-        let package = match id.registry() {
-            Registry::GitHub => match github::get_latest_package(id.name()) {
-                Ok(r) => r,
-                Err(e) => match e {
-                    github::Error::InvalidIdentifier => {
-                        return Err(InstallError::InvalidIdentifier)
-                    }
-                    e => return Err(InstallError::GetPackageFailed(e.into())),
-                },
-            },
+        let id = &id.relative_to(&std::env::current_dir().unwrap());
+        let query = match locked {
+            Some(v) => id.with_exact_version(v),
+            None => id.clone(),
         };
+        let mut package = Self::get_package(&query)?;
 
-        let (content, definition) = Self::extract_package(package);
+        verification::verify(context, &mut package).map_err(InstallError::VerifyFailed)?;
+
+        let (content, definition) = Self::extract_package(package.content)?;
+
+        if let Some(v) = query.version() {
+            if !v.matches(&definition.version) {
+                return Err(InstallError::VersionMismatch(definition.version));
+            }
+        }
 
         // Check if installation can be proceed.
-        let context = context
+        let location = context
             .data()
             .module(false)
             .unwrap()
             .by_name(Cow::Owned(definition.name.clone()));
-        let path = context.path();
+        let path = location.path();
 
         if path.exists() {
             return Err(InstallError::AlreadyInstalled(definition.name.clone()));
         }
 
-        // Install.
-        let mut scope = InstallationScope::new(&path);
+        // Install dependencies.
+        Self::resolve_dependencies(context, &definition, stack)
+            .map_err(|e| InstallError::DependencyFailed(Box::new(e)))?;
+
+        // Install into the staging directory so the failure will not leave an incomplete module.
+        let staging = context
+            .data()
+            .module(false)
+            .unwrap()
+            .staging(Cow::Borrowed(&definition.name));
+        let temp = staging.path();
 
-        create_dir_all(&path).unwrap();
-        Self::install_package(&content, &path);
+        Self::create_staging(&temp).map_err(InstallError::StagingFailed)?;
+
+        let mut scope = InstallationScope::new(&temp);
+
+        Self::install_package(&content, &temp).map_err(InstallError::CopyFailed)?;
 
         // Write metadata.
-        let metadata = MetadataManager::new(context.metadata(false).unwrap());
+        MetadataManager::new(staging.metadata(false).unwrap())
+            .registry()
+            .write(&id)
+            .map_err(InstallError::StagingFailed)?;
 
-        metadata.registry().write(&id).unwrap();
+        // Move to the final location.
+        std::fs::rename(&temp, &path).map_err(InstallError::MoveFailed)?;
 
         // Mark installation success.
         scope.success();
@@ -103,56 +148,181 @@ impl<'context, 'name> Module<'context, 'name> {
         Ok(Module {
             path,
             definition,
-            metadata,
+            metadata: MetadataManager::new(location.metadata(false).unwrap()),
         })
     }
 
     pub fn update(context: &'context Context, name: Cow<'name, str>) -> Result<Self, UpdateError> {
+        Self::update_from(context, name, None, None, true, &mut Vec::new())
+    }
+
+    /// Make sure the module `name` is installed from `id` and its version match the version
+    /// constraint of `id`, or exactly `locked` if it is specified (e.g. from a lockfile). The module
+    /// will be updated to the latest version that match the constraint if `update` is `true`. The
+    /// module is always recorded as installed from `id` so the later update will respect its
+    /// constraint.
+    pub fn require(
+        context: &'context Context,
+        name: &str,
+        id: &PackageId,
+        locked: Option<&Version>,
+        update: bool,
+    ) -> Result<Self, RequireError> {
+        Self::require_with(context, name, id, locked, update, &mut Vec::new())
+    }
+
+    fn require_with(
+        context: &'context Context,
+        name: &str,
+        id: &PackageId,
+        locked: Option<&Version>,
+        update: bool,
+        stack: &mut Vec<String>,
+    ) -> Result<Self, RequireError> {
+        let module = match Self::find(context, Cow::Owned(name.into())) {
+            Ok(r) => r,
+            Err(FindError::NotInstalled(_)) => {
+                let module = Self::install_with(context, id, locked, stack)
+                    .map_err(RequireError::InstallFailed)?;
+
+                if module.name() != name {
+                    return Err(RequireError::NameMismatch(module.definition.name));
+                }
+
+                return Ok(module);
+            }
+            Err(e) => return Err(RequireError::FindFailed(e)),
+        };
+
+        // Check if the installed version can be used.
+        let matched = match (locked, id.version()) {
+            (Some(v), _) => module.definition.version == *v,
+            (None, Some(v)) => v.matches(&module.definition.version),
+            (None, None) => true,
+        };
+
+        if matched && (!update || context.offline()) {
+            return Ok(module);
+        }
+
+        let name = Cow::Owned(name.into());
+
+        match Self::update_from(context, name, Some(id), locked, matched, stack) {
+            Ok(r) => Ok(r),
+            Err(UpdateError::AlreadyLatest) => Ok(module),
+            Err(e) => Err(RequireError::UpdateFailed(e)),
+        }
+    }
+
+    /// Update the module `name` from `id`, or from the package it was installed if `id` is [`None`].
+    /// See [`Self::install_with`] for `locked`. The module will be replaced with any version that is
+    /// different from the installed one if `newer` is `false`. The version constraints of the
+    /// installed modules that depend on this module are respected, except the modules in `stack`.
+    fn update_from(
+        context: &'context Context,
+        name: Cow<'name, str>,
+        id: Option<&PackageId>,
+        locked: Option<&Version>,
+        newer: bool,
+        stack: &mut Vec<String>,
+    ) -> Result<Self, UpdateError> {
+        if context.offline() {
+            return Err(UpdateError::Offline);
+        }
+
         // Check if module installed.
-        let context = context.data().module(false).unwrap().by_name(name);
-        let path = context.path();
+        let location = context.data().module(false).unwrap().by_name(name);
+        let path = location.path();
 
         if !path.exists() {
             return Err(UpdateError::NotInstalled);
         }
 
-        let local: ModuleDefinition = yaml::load_file(context.definition()).unwrap();
+        let file = location.definition();
+        let local: ModuleDefinition =
+            yaml::load_file(&file).map_err(|e| UpdateError::LoadDefinitionFailed(file, e))?;
 
         // Get registry.
-        let metadata = MetadataManager::new(context.metadata(false).unwrap());
-        let id = metadata.registry().read().unwrap();
+        let metadata = MetadataManager::new(location.metadata(false).unwrap());
+        let id = match id {
+            Some(v) => v.clone(),
+            None => metadata
+                .registry()
+                .read()
+                .map_err(UpdateError::LoadRegistryFailed)?,
+        };
 
         // Download latest package.
-        let package = match id.registry() {
-            Registry::GitHub => match github::get_latest_package(id.name()) {
-                Ok(r) => r,
-                Err(e) => return Err(UpdateError::GetPackageFailed(e.into())),
-            },
+        let dependents = Self::dependents(context, &local.name, stack);
+        let query = match locked {
+            Some(v) => id.with_exact_version(v),
+            None => id.clone(),
         };
+        let query = query.constrained(dependents.iter().filter_map(|(_, d)| d.version()));
+        let mut package = Self::get_package(&query).map_err(|e| match e {
+            InstallError::GetPackageFailed(e) => UpdateError::GetPackageFailed(e),
+            e => UpdateError::GetPackageFailed(e.into()),
+        })?;
 
-        let (content, remote) = Self::extract_package(package);
+        verification::verify(context, &mut package).map_err(UpdateError::VerifyFailed)?;
 
-        // Check if the installed version already up todate.
-        if local.version >= remote.version {
+        let (content, remote) = Self::extract_package(package.content)?;
+
+        if let Some(v) = query.version() {
+            if !v.matches(&remote.version) {
+                return Err(UpdateError::VersionMismatch(remote.version));
+            }
+        }
+
+        // Check if the installed version already up todate. The module from a local directory is always
+        // updated since it is under development.
+        let latest = local.version == remote.version || (newer && local.version > remote.version);
+
+        if latest && !registry::open(id.registry()).always_update() {
             return Err(UpdateError::AlreadyLatest);
         }
 
-        // Update.
-        let mut scope = InstallationScope::new(&path);
+        // Install dependencies.
+        Self::resolve_dependencies(context, &remote, stack)
+            .map_err(|e| UpdateError::DependencyFailed(Box::new(e)))?;
 
-        for file in std::fs::read_dir(&path).unwrap().map(|i| i.unwrap()) {
-            if file.file_name() == metadata.directory_name() {
-                continue;
-            }
+        // Install the new version into the staging directory so the failure will not touch the
+        // installed one.
+        let staging = context
+            .data()
+            .module(false)
+            .unwrap()
+            .staging(Cow::Borrowed(&local.name));
+        let temp = staging.path();
 
-            if file.file_type().unwrap().is_dir() {
-                std::fs::remove_dir_all(file.path()).unwrap();
-            } else {
-                std::fs::remove_file(file.path()).unwrap();
-            }
+        Self::create_staging(&temp).map_err(UpdateError::ReplaceFailed)?;
+
+        let mut scope = InstallationScope::new(&temp);
+
+        Self::install_package(&content, &temp).map_err(UpdateError::CopyFailed)?;
+
+        // Keep the metadata of the installed version.
+        if metadata.path().exists() {
+            let options = fs_extra::dir::CopyOptions::new();
+
+            fs_extra::dir::copy(metadata.path(), &temp, &options)
+                .map_err(|e| UpdateError::ReplaceFailed(std::io::Error::other(e)))?;
         }
 
-        Self::install_package(&content, &path);
+        MetadataManager::new(staging.metadata(false).unwrap())
+            .registry()
+            .write(&id)
+            .map_err(UpdateError::ReplaceFailed)?;
+
+        // Swap the installed version with the new one.
+        let previous = context
+            .data()
+            .module(false)
+            .unwrap()
+            .previous(Cow::Borrowed(&local.name))
+            .path();
+
+        Self::replace(&path, &temp, &previous).map_err(UpdateError::ReplaceFailed)?;
 
         // Mark update success.
         scope.success();
@@ -165,6 +335,161 @@ impl<'context, 'name> Module<'context, 'name> {
         })
     }
 
+    /// Restore the version of the module `name` before the last update. The current version will be
+    /// kept as the previous version so the rollback can be undone by another rollback.
+    pub fn rollback(
+        context: &'context Context,
+        name: Cow<'name, str>,
+    ) -> Result<Self, RollbackError> {
+        let location = context.data().module(false).unwrap().by_name(name.clone());
+        let path = location.path();
+
+        if !path.exists() {
+            return Err(RollbackError::NotInstalled);
+        }
+
+        let previous = context.data().module(false).unwrap().previous(name.clone());
+
+        if !previous.path().exists() {
+            return Err(RollbackError::NoPreviousVersion);
+        }
+
+        // Check if the previous version can be used by the modules that depend on it.
+        let file = previous.definition();
+        let definition: ModuleDefinition =
+            yaml::load_file(&file).map_err(|e| RollbackError::LoadDefinitionFailed(file, e))?;
+        let conflicts: Vec<String> = Self::dependents(context, &name, &[])
+            .into_iter()
+            .filter(|(_, id)| match id.version() {
+                Some(v) => !v.matches(&definition.version),
+                None => false,
+            })
+            .map(|(n, _)| n)
+            .collect();
+
+        if !conflicts.is_empty() {
+            return Err(RollbackError::RequiredBy(definition.version, conflicts));
+        }
+
+        // Swap the installed version with the previous one.
+        let staging = context
+            .data()
+            .module(false)
+            .unwrap()
+            .staging(name.clone())
+            .path();
+        let previous = previous.path();
+
+        if staging.exists() {
+            std::fs::remove_dir_all(&staging).map_err(RollbackError::ReplaceFailed)?;
+        } else {
+            create_dir_all(staging.parent().unwrap()).map_err(RollbackError::ReplaceFailed)?;
+        }
+
+        std::fs::rename(&previous, &staging).map_err(RollbackError::ReplaceFailed)?;
+
+        if let Err(e) = Self::replace(&path, &staging, &previous) {
+            std::fs::rename(&staging, &previous).map_err(RollbackError::ReplaceFailed)?;
+            return Err(RollbackError::ReplaceFailed(e));
+        }
+
+        Ok(Module {
+            path,
+            definition,
+            metadata: MetadataManager::new(location.metadata(false).unwrap()),
+        })
+    }
+
+    /// Find all installed modules, ordered by the name.
+    pub fn list(context: &'context Context) -> std::io::Result<Vec<Result<Self, FindError>>> {
+        let path = context.data().module(false).unwrap().path();
+        let mut names: Vec<String> = Vec::new();
+
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+
+        for entry in std::fs::read_dir(&path)? {
+            let entry = entry?;
+
+            let name = entry.file_name().to_string_lossy().into_owned();
+
+            // Skip the directories for staging and previous versions.
+            if entry.file_type()?.is_dir() && !name.starts_with('.') {
+                names.push(name);
+            }
+        }
+
+        names.sort();
+
+        Ok(names
+            .into_iter()
+            .map(|n| Self::find(context, Cow::Owned(n)))
+            .collect())
+    }
+
+    /// Remove the installed module `name`. The configurations of the module will be removed too if
+    /// `purge` is `true`.
+    pub fn remove(context: &Context, name: &str, purge: bool) -> Result<(), RemoveError> {
+        let path = context
+            .data()
+            .module(false)
+            .unwrap()
+            .by_name(Cow::Borrowed(name))
+            .path();
+
+        if !path.exists() {
+            return Err(RemoveError::NotInstalled);
+        }
+
+        // Check if other modules depend on this module.
+        let dependents = Self::dependents(context, name, &[]);
+
+        if !dependents.is_empty() {
+            return Err(RemoveError::RequiredBy(
+                dependents.into_iter().map(|(n, _)| n).collect(),
+            ));
+        }
+
+        std::fs::remove_dir_all(&path).map_err(|e| RemoveError::RemoveFailed(path, e))?;
+
+        // Remove the previous version.
+        let path = context
+            .data()
+            .module(false)
+            .unwrap()
+            .previous(Cow::Borrowed(name))
+            .path();
+
+        if path.exists() {
+            std::fs::remove_dir_all(&path).map_err(|e| RemoveError::RemoveFailed(path, e))?;
+        }
+
+        if purge {
+            let path = context
+                .data()
+                .config(false)
+                .unwrap()
+                .for_module(Cow::Borrowed(name))
+                .path();
+
+            if path.exists() {
+                std::fs::remove_dir_all(&path).map_err(|e| RemoveError::RemoveFailed(path, e))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn name(&self) -> &str {
+        &self.definition.name
+    }
+
+    /// Gets the identifier of the package that this module was installed from.
+    pub fn registry(&self) -> Option<PackageId> {
+        self.metadata.registry().read().ok()
+    }
+
     pub fn definition(&self) -> &ModuleDefinition {
         &self.definition
     }
@@ -173,27 +498,203 @@ impl<'context, 'name> Module<'context, 'name> {
         &self.path
     }
 
-    fn extract_package<F: Read + Seek>(package: F) -> (TempDir, ModuleDefinition) {
+    /// Install or update the dependencies of the module with `definition` until all of them match the
+    /// version constraints.
+    fn resolve_dependencies(
+        context: &'context Context,
+        definition: &ModuleDefinition,
+        stack: &mut Vec<String>,
+    ) -> Result<(), DependencyError> {
+        stack.push(definition.name.clone());
+
+        for (name, id) in &definition.dependencies {
+            // Check if the dependency cause a cycle.
+            if let Some(i) = stack.iter().position(|n| n == name) {
+                let mut cycle = stack[i..].to_vec();
+                cycle.push(name.clone());
+                return Err(DependencyError::Cycle(cycle));
+            }
+
+            if let Some(path) = Self::dependency_path(context, name, stack, &mut HashSet::new()) {
+                let i = stack
+                    .iter()
+                    .position(|n| n == path.last().unwrap())
+                    .unwrap();
+                let mut cycle = stack[i..].to_vec();
+                cycle.extend(path);
+                return Err(DependencyError::Cycle(cycle));
+            }
+
+            // Install or update the dependency.
+            match Self::require_with(context, name, id, None, false, stack) {
+                Ok(_) => {}
+                Err(RequireError::UpdateFailed(e)) if is_conflict(&e) => {
+                    let dependents = Self::dependents(context, name, stack);
+                    let query = id.constrained(dependents.iter().filter_map(|(_, d)| d.version()));
+
+                    return Err(DependencyError::Conflict(
+                        name.clone(),
+                        query.version().unwrap().clone(),
+                    ));
+                }
+                Err(e) => return Err(DependencyError::RequireFailed(name.clone(), e)),
+            }
+        }
+
+        stack.pop();
+
+        Ok(())
+    }
+
+    /// Find the path from the installed module `name` to any of `targets` by following the
+    /// dependencies of the installed modules.
+    fn dependency_path(
+        context: &Context,
+        name: &str,
+        targets: &[String],
+        visited: &mut HashSet<String>,
+    ) -> Option<Vec<String>> {
+        if !visited.insert(name.into()) {
+            return None;
+        }
+
+        let module = Module::find(context, Cow::Borrowed(name)).ok()?;
+
+        for dependency in module.definition.dependencies.keys() {
+            if targets.contains(dependency) {
+                return Some(vec![name.into(), dependency.clone()]);
+            }
+
+            if let Some(mut path) = Self::dependency_path(context, dependency, targets, visited) {
+                path.insert(0, name.into());
+                return Some(path);
+            }
+        }
+
+        None
+    }
+
+    /// Gets the installed modules that depend on the module `name`, except the modules in `excludes`,
+    /// together with the identifier they required.
+    fn dependents(context: &Context, name: &str, excludes: &[String]) -> Vec<(String, PackageId)> {
+        let modules = match Module::list(context) {
+            Ok(r) => r,
+            Err(_) => return Vec::new(),
+        };
+
+        modules
+            .into_iter()
+            .filter_map(|m| m.ok())
+            .filter(|m| !excludes.contains(&m.definition.name))
+            .filter_map(|m| {
+                let id = m.definition.dependencies.get(name)?.clone();
+                Some((m.definition.name, id))
+            })
+            .collect()
+    }
+
+    /// Download the package `id` from its registry.
+    fn get_package(id: &PackageId) -> Result<Package, InstallError> {
+        match registry::open(id.registry()).get_package(id.name(), id.version()) {
+            Ok(r) => Ok(r),
+            Err(GetPackageError::InvalidIdentifier) => Err(InstallError::InvalidIdentifier),
+            Err(e) => Err(InstallError::GetPackageFailed(e.into())),
+        }
+    }
+
+    fn extract_package(
+        package: PackageContent,
+    ) -> Result<(TempDir, ModuleDefinition), PackageError> {
         // Extract.
-        let content = tempfile::tempdir().unwrap();
-        let mut extractor = ZipArchive::new(package).unwrap();
+        let content = match package {
+            PackageContent::Archive(file) => {
+                let content = tempfile::tempdir().map_err(|e| PackageError::Extract(e.into()))?;
+                let mut extractor = ZipArchive::new(file).map_err(PackageError::Extract)?;
 
-        extractor.extract(&content).unwrap();
+                extractor.extract(&content).map_err(PackageError::Extract)?;
+                content
+            }
+            PackageContent::Directory(v) => v,
+        };
 
         // Read definition.
         let path = PackageReader::new(content.path()).definition();
-        let definition: ModuleDefinition = yaml::load_file(&path).unwrap();
+        let definition: ModuleDefinition =
+            yaml::load_file(&path).map_err(PackageError::InvalidDefinition)?;
+
+        Ok((content, definition))
+    }
+
+    /// Create an empty staging directory at `path`. The leftover from the previous failure will be
+    /// removed.
+    fn create_staging(path: &Path) -> std::io::Result<()> {
+        if path.exists() {
+            std::fs::remove_dir_all(path)?;
+        }
+
+        create_dir_all(path)
+    }
+
+    /// Move the module at `path` to `previous` then move `staging` to `path`. The module at `path` and
+    /// the existing `previous` are restored if the operation failed.
+    fn replace(path: &Path, staging: &Path, previous: &Path) -> std::io::Result<()> {
+        // Move the existing previous version aside instead of removing it so we can restore it.
+        let mut old = previous.as_os_str().to_owned();
+
+        old.push(".old");
+
+        let old = PathBuf::from(old);
+
+        if old.exists() {
+            std::fs::remove_dir_all(&old)?;
+        }
+
+        if previous.exists() {
+            std::fs::rename(previous, &old)?;
+        } else {
+            create_dir_all(previous.parent().unwrap())?;
+        }
+
+        // Swap.
+        let result = match std::fs::rename(path, previous) {
+            Ok(_) => match std::fs::rename(staging, path) {
+                Ok(_) => Ok(()),
+                Err(e) => {
+                    std::fs::rename(previous, path)?;
+                    Err(e)
+                }
+            },
+            Err(e) => Err(e),
+        };
+
+        if let Err(e) = result {
+            if old.exists() {
+                std::fs::rename(&old, previous)?;
+            }
+
+            return Err(e);
+        }
+
+        // The module already replaced so it is not worth to fail because of the old copy.
+        if old.exists() {
+            std::fs::remove_dir_all(&old).ok();
+        }
 
-        (content, definition)
+        Ok(())
     }
 
-    fn install_package<C: AsRef<Path>, D: AsRef<Path>>(content: C, destination: D) {
+    fn install_package<C: AsRef<Path>, D: AsRef<Path>>(
+        content: C,
+        destination: D,
+    ) -> Result<(), fs_extra::error::Error> {
         let mut options = fs_extra::dir::CopyOptions::new();
 
         options.copy_inside = true;
         options.content_only = true;
 
-        fs_extra::dir::copy(content, destination, &options).unwrap();
+        fs_extra::dir::copy(content, destination, &options)?;
+
+        Ok(())
     }
 }
 
@@ -224,14 +725,274 @@ impl Display for FindError {
     }
 }
 
+#[derive(Debug)]
+pub enum RemoveError {
+    NotInstalled,
+    RequiredBy(Vec<String>),
+    RemoveFailed(PathBuf, std::io::Error),
+}
+
+impl Error for RemoveError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RemoveError::RemoveFailed(_, e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl Display for RemoveError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            RemoveError::NotInstalled => write!(f, "The module is not installed"),
+            RemoveError::RequiredBy(n) => {
+                write!(f, "The module is required by {}", n.join(", "))
+            }
+            RemoveError::RemoveFailed(p, e) => {
+                write!(f, "Failed to remove {}: {}", p.display(), e)
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum RollbackError {
+    NotInstalled,
+    NoPreviousVersion,
+    LoadDefinitionFailed(PathBuf, yaml::FileError),
+    RequiredBy(Version, Vec<String>),
+    ReplaceFailed(std::io::Error),
+}
+
+impl Error for RollbackError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RollbackError::LoadDefinitionFailed(_, e) => Some(e),
+            RollbackError::ReplaceFailed(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl Display for RollbackError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            RollbackError::NotInstalled => write!(f, "The module is not installed"),
+            RollbackError::NoPreviousVersion => {
+                write!(f, "The module does not have a previous version")
+            }
+            RollbackError::LoadDefinitionFailed(p, e) => {
+                write!(f, "Failed to load {}: {}", p.display(), e)
+            }
+            RollbackError::RequiredBy(v, n) => write!(
+                f,
+                "Version {} of the module does not satisfy the version constraint of {}",
+                v,
+                n.join(", ")
+            ),
+            RollbackError::ReplaceFailed(e) => {
+                write!(f, "Failed to replace the installed module: {}", e)
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
 pub enum InstallError {
     InvalidIdentifier,
     GetPackageFailed(Box<dyn Error>),
+    VerifyFailed(VerifyError),
     AlreadyInstalled(String),
+    VersionMismatch(Version),
+    Offline,
+    DependencyFailed(Box<DependencyError>),
+    ExtractFailed(zip::result::ZipError),
+    InvalidDefinition(yaml::FileError),
+    StagingFailed(std::io::Error),
+    CopyFailed(fs_extra::error::Error),
+    MoveFailed(std::io::Error),
+}
+
+impl Error for InstallError {}
+
+impl Display for InstallError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            InstallError::InvalidIdentifier => write!(f, "The package identifier is not valid"),
+            InstallError::GetPackageFailed(e) => write!(f, "Failed to get the package: {}", e),
+            InstallError::VerifyFailed(e) => e.fmt(f),
+            InstallError::AlreadyInstalled(n) => {
+                write!(f, "The module '{}' is already installed", n)
+            }
+            InstallError::VersionMismatch(v) => write!(
+                f,
+                "The package is version {}, which does not match the version constraint",
+                v
+            ),
+            InstallError::Offline => write!(f, "Cannot install a module in offline mode"),
+            InstallError::DependencyFailed(e) => e.fmt(f),
+            InstallError::ExtractFailed(e) => write!(f, "Failed to extract the package: {}", e),
+            InstallError::InvalidDefinition(e) => {
+                write!(
+                    f,
+                    "The package does not have a valid locenv-module.yml: {}",
+                    e
+                )
+            }
+            InstallError::StagingFailed(e) => {
+                write!(f, "Failed to prepare the staging directory: {}", e)
+            }
+            InstallError::CopyFailed(e) => write!(f, "Failed to copy the package: {}", e),
+            InstallError::MoveFailed(e) => {
+                write!(
+                    f,
+                    "Failed to move the module to the module directory: {}",
+                    e
+                )
+            }
+        }
+    }
 }
 
+#[derive(Debug)]
 pub enum UpdateError {
     NotInstalled,
+    LoadDefinitionFailed(PathBuf, yaml::FileError),
+    LoadRegistryFailed(dirtree::TextFileError<PackageId>),
     GetPackageFailed(Box<dyn Error>),
+    VerifyFailed(VerifyError),
     AlreadyLatest,
+    VersionMismatch(Version),
+    Offline,
+    DependencyFailed(Box<DependencyError>),
+    ExtractFailed(zip::result::ZipError),
+    InvalidDefinition(yaml::FileError),
+    CopyFailed(fs_extra::error::Error),
+    ReplaceFailed(std::io::Error),
+}
+
+impl Error for UpdateError {}
+
+impl Display for UpdateError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            UpdateError::NotInstalled => write!(f, "The module is not installed"),
+            UpdateError::LoadDefinitionFailed(p, e) => {
+                write!(f, "Failed to load {}: {}", p.display(), e)
+            }
+            UpdateError::LoadRegistryFailed(e) => {
+                write!(
+                    f,
+                    "Failed to load the registry of the installed module: {}",
+                    e
+                )
+            }
+            UpdateError::GetPackageFailed(e) => write!(f, "Failed to get the package: {}", e),
+            UpdateError::VerifyFailed(e) => e.fmt(f),
+            UpdateError::AlreadyLatest => write!(f, "The module is already in latest version"),
+            UpdateError::VersionMismatch(v) => write!(
+                f,
+                "The package is version {}, which does not match the version constraint",
+                v
+            ),
+            UpdateError::Offline => write!(f, "Cannot update a module in offline mode"),
+            UpdateError::DependencyFailed(e) => e.fmt(f),
+            UpdateError::ExtractFailed(e) => write!(f, "Failed to extract the package: {}", e),
+            UpdateError::InvalidDefinition(e) => {
+                write!(
+                    f,
+                    "The package does not have a valid locenv-module.yml: {}",
+                    e
+                )
+            }
+            UpdateError::CopyFailed(e) => write!(f, "Failed to copy the package: {}", e),
+            UpdateError::ReplaceFailed(e) => {
+                write!(f, "Failed to replace the installed module: {}", e)
+            }
+        }
+    }
+}
+
+/// Represents a failure to read the downloaded package.
+enum PackageError {
+    Extract(zip::result::ZipError),
+    InvalidDefinition(yaml::FileError),
+}
+
+impl From<PackageError> for InstallError {
+    fn from(e: PackageError) -> Self {
+        match e {
+            PackageError::Extract(e) => Self::ExtractFailed(e),
+            PackageError::InvalidDefinition(e) => Self::InvalidDefinition(e),
+        }
+    }
+}
+
+impl From<PackageError> for UpdateError {
+    fn from(e: PackageError) -> Self {
+        match e {
+            PackageError::Extract(e) => Self::ExtractFailed(e),
+            PackageError::InvalidDefinition(e) => Self::InvalidDefinition(e),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum RequireError {
+    FindFailed(FindError),
+    InstallFailed(InstallError),
+    UpdateFailed(UpdateError),
+    NameMismatch(String),
+}
+
+impl Error for RequireError {}
+
+impl Display for RequireError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            RequireError::FindFailed(e) => e.fmt(f),
+            RequireError::InstallFailed(e) => e.fmt(f),
+            RequireError::UpdateFailed(e) => e.fmt(f),
+            RequireError::NameMismatch(n) => {
+                write!(f, "The package provides module '{}' instead", n)
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum DependencyError {
+    Cycle(Vec<String>),
+    Conflict(String, semver::VersionReq),
+    RequireFailed(String, RequireError),
+}
+
+impl Error for DependencyError {}
+
+impl Display for DependencyError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            DependencyError::Cycle(n) => write!(f, "Dependency cycle detected: {}", n.join(" -> ")),
+            DependencyError::Conflict(n, v) => write!(
+                f,
+                "No release of module '{}' satisfies all of the version constraints {}",
+                n, v
+            ),
+            DependencyError::RequireFailed(n, e) => {
+                write!(f, "Failed to install dependency '{}': {}", n, e)
+            }
+        }
+    }
+}
+
+/// Check if `error` was caused by no version of the module can satisfy the version constraints.
+fn is_conflict(error: &UpdateError) -> bool {
+    match error {
+        UpdateError::GetPackageFailed(e) => matches!(
+            e.downcast_ref::<GetPackageError>(),
+            Some(GetPackageError::NoMatchingRelease(_))
+        ),
+        UpdateError::VersionMismatch(_) => true,
+        _ => false,
+    }
 }
@@ -0,0 +1,84 @@
+use crate::package::{Package, PackageContent};
+use crate::registry::{GetPackageError, PackageRegistry};
+use semver::VersionReq;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+
+/// Registry for the modules in a local directory (e.g. `path:../mod-autoconf`).
+pub struct Local;
+
+impl PackageRegistry for Local {
+    fn get_package(&self, name: &str, _: Option<&VersionReq>) -> Result<Package, GetPackageError> {
+        Ok(get_package(name)?)
+    }
+
+    fn always_update(&self) -> bool {
+        // The module is under development so the version may not be changed.
+        true
+    }
+}
+
+/// Use the content of the directory `path` as a package. This is intended for developing a module.
+fn get_package(path: &str) -> Result<Package, Error> {
+    let path = Path::new(path);
+
+    if !path.is_dir() {
+        return Err(Error::NotFound(path.into()));
+    }
+
+    // Copy the content except the data of Git and locenv.
+    let mut items: Vec<PathBuf> = Vec::new();
+
+    for entry in std::fs::read_dir(path).map_err(Error::ReadFailed)? {
+        let entry = entry.map_err(Error::ReadFailed)?;
+        let name = entry.file_name();
+
+        if name != ".git" && name != ".locenv" {
+            items.push(entry.path());
+        }
+    }
+
+    let content = tempfile::tempdir().unwrap();
+    let options = fs_extra::dir::CopyOptions::new();
+
+    fs_extra::copy_items(&items, content.path(), &options).map_err(Error::CopyFailed)?;
+
+    Ok(Package {
+        content: PackageContent::Directory(content),
+        checksum: None,
+        signature: None,
+    })
+}
+
+#[derive(Debug)]
+pub enum Error {
+    NotFound(PathBuf),
+    ReadFailed(std::io::Error),
+    CopyFailed(fs_extra::error::Error),
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::ReadFailed(e) => Some(e),
+            Self::CopyFailed(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Self::NotFound(p) => write!(f, "directory {} does not exist", p.display()),
+            Self::ReadFailed(e) => write!(f, "read failed: {}", e),
+            Self::CopyFailed(e) => write!(f, "copy failed: {}", e),
+        }
+    }
+}
+
+impl From<Error> for GetPackageError {
+    fn from(e: Error) -> Self {
+        Self::Failed(Box::new(e))
+    }
+}
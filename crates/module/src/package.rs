@@ -1,13 +1,24 @@
 use context::data::ModuleDirectory;
+use schemars::gen::SchemaGenerator;
+use schemars::schema::{InstanceType, Schema, SchemaObject};
+use schemars::JsonSchema;
+use semver::{Comparator, Version, VersionReq};
+use serde::{Deserialize, Deserializer};
 use std::error::Error;
 use std::fmt::{Display, Formatter};
-use std::fs::remove_dir_all;
+use std::fs::{remove_dir_all, File};
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use tempfile::TempDir;
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Registry {
     GitHub,
+    GitLab,
+    Gitea,
+    Git,
+    Path,
+    Https,
 }
 
 pub enum RegistryError {
@@ -18,12 +29,29 @@ pub enum RegistryError {
 pub struct PackageId {
     registry: Registry,
     name: String,
+    version: Option<VersionReq>,
 }
 
 #[derive(Debug)]
 pub enum IdentifierError {
     InvalidFormat,
     UnknowRegistry,
+    InvalidVersion(semver::Error),
+}
+
+/// Represents a downloaded package and the files that was published to verify it.
+pub struct Package {
+    pub content: PackageContent,
+    pub checksum: Option<Vec<u8>>,
+    pub signature: Option<Vec<u8>>,
+}
+
+pub enum PackageContent {
+    /// A ZIP file that contains the module.
+    Archive(File),
+
+    /// A directory that contains the module.
+    Directory(TempDir),
 }
 
 pub struct PackageReader<'content> {
@@ -41,6 +69,11 @@ impl Display for Registry {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
         match self {
             Registry::GitHub => f.write_str("github"),
+            Registry::GitLab => f.write_str("gitlab"),
+            Registry::Gitea => f.write_str("gitea"),
+            Registry::Git => f.write_str("git"),
+            Registry::Path => f.write_str("path"),
+            Registry::Https => f.write_str("https"),
         }
     }
 }
@@ -51,6 +84,11 @@ impl FromStr for Registry {
     fn from_str(s: &str) -> Result<Self, RegistryError> {
         let v = match s.to_lowercase().as_str() {
             "github" => Registry::GitHub,
+            "gitlab" => Registry::GitLab,
+            "gitea" => Registry::Gitea,
+            "git" => Registry::Git,
+            "path" => Registry::Path,
+            "https" => Registry::Https,
             _ => return Err(RegistryError::Invalid),
         };
 
@@ -68,11 +106,96 @@ impl PackageId {
     pub fn name(&self) -> &str {
         self.name.as_ref()
     }
+
+    /// Gets the version constraint of the package. [`None`] means the latest version.
+    pub fn version(&self) -> Option<&VersionReq> {
+        self.version.as_ref()
+    }
+
+    /// Gets the same package with exactly `version`.
+    pub(crate) fn with_exact_version(&self, version: &Version) -> Self {
+        Self {
+            registry: self.registry,
+            name: self.name.clone(),
+            version: Some(VersionReq::parse(&format!("={}", version)).unwrap()),
+        }
+    }
+
+    /// Gets the same package with the path relative to `base` if this is a package from a relative
+    /// path.
+    pub fn relative_to(&self, base: &Path) -> Self {
+        let name = match self.registry {
+            Registry::Path => base.join(&self.name).to_string_lossy().into_owned(),
+            _ => self.name.clone(),
+        };
+
+        Self {
+            registry: self.registry,
+            name,
+            version: self.version.clone(),
+        }
+    }
+
+    /// Gets the same package with the version constraint that also match all of `others`.
+    pub(crate) fn constrained<'a, I>(&self, others: I) -> Self
+    where
+        I: IntoIterator<Item = &'a VersionReq>,
+    {
+        let mut comparators: Vec<Comparator> = match &self.version {
+            Some(v) => v.comparators.clone(),
+            None => Vec::new(),
+        };
+
+        for other in others {
+            comparators.extend(other.comparators.iter().cloned());
+        }
+
+        Self {
+            registry: self.registry,
+            name: self.name.clone(),
+            version: if comparators.is_empty() {
+                None
+            } else {
+                Some(VersionReq { comparators })
+            },
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for PackageId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+
+        value.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl JsonSchema for PackageId {
+    fn schema_name() -> String {
+        "PackageId".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        let mut schema = SchemaObject {
+            instance_type: Some(InstanceType::String.into()),
+            ..Default::default()
+        };
+
+        schema.metadata().description =
+            Some("Package identifier (e.g. github:locenv/mod-autoconf@^1, gitlab:<group>/<project>, gitea:<owner>/<repo>, git:<url>#<ref>, path:<dir> or https://<url>).".into());
+        schema.into()
+    }
 }
 
 impl Display for PackageId {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
-        write!(f, "{}:{}", self.registry, self.name)
+        write!(f, "{}:{}", self.registry, self.name)?;
+
+        if let Some(v) = &self.version {
+            write!(f, "@{}", v)?;
+        }
+
+        Ok(())
     }
 }
 
@@ -86,6 +209,13 @@ impl FromStr for PackageId {
             None => return Err(IdentifierError::InvalidFormat),
         };
 
+        // The name may contains '@' (e.g. git@github.com:owner/repo.git) so we need to make sure it is
+        // not a part of URL or path.
+        let (name, version) = match name.rsplit_once('@') {
+            Some((n, v)) if !v.contains(['/', ':']) => (n, Some(parse_version_constraint(v)?)),
+            _ => (name, None),
+        };
+
         if registry.is_empty() || name.is_empty() {
             return Err(IdentifierError::InvalidFormat);
         }
@@ -99,10 +229,21 @@ impl FromStr for PackageId {
         Ok(PackageId {
             registry,
             name: name.into(),
+            version,
         })
     }
 }
 
+/// Parse the version constraint of the package. A full version (e.g. `v1.2.3`) means exactly that
+/// version while the other are semver ranges (e.g. `^1.2`).
+fn parse_version_constraint(v: &str) -> Result<VersionReq, IdentifierError> {
+    if let Ok(v) = Version::parse(v.strip_prefix('v').unwrap_or(v)) {
+        return VersionReq::parse(&format!("={}", v)).map_err(IdentifierError::InvalidVersion);
+    }
+
+    VersionReq::parse(v).map_err(IdentifierError::InvalidVersion)
+}
+
 // IdentifierError
 
 impl Error for IdentifierError {}
@@ -112,6 +253,7 @@ impl Display for IdentifierError {
         match self {
             IdentifierError::InvalidFormat => f.write_str("Unrecognized format"),
             IdentifierError::UnknowRegistry => f.write_str("Unrecognized registry"),
+            IdentifierError::InvalidVersion(e) => write!(f, "Invalid version constraint: {}", e),
         }
     }
 }
@@ -152,3 +294,121 @@ impl<'destination> Drop for InstallationScope<'destination> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_without_version() {
+        let id: PackageId = "github:locenv/mod-autoconf".parse().unwrap();
+
+        assert_eq!(id.registry(), Registry::GitHub);
+        assert_eq!(id.name(), "locenv/mod-autoconf");
+        assert!(id.version().is_none());
+        assert_eq!(id.to_string(), "github:locenv/mod-autoconf");
+    }
+
+    #[test]
+    fn parse_version_range() {
+        let id: PackageId = "gitlab:group/project@^1.2".parse().unwrap();
+
+        assert_eq!(id.registry(), Registry::GitLab);
+        assert_eq!(id.name(), "group/project");
+        assert_eq!(id.version(), Some(&VersionReq::parse("^1.2").unwrap()));
+    }
+
+    #[test]
+    fn parse_exact_version() {
+        let id: PackageId = "github:locenv/mod-autoconf@v1.2.3".parse().unwrap();
+        let version = id.version().unwrap();
+
+        assert_eq!(version, &VersionReq::parse("=1.2.3").unwrap());
+        assert!(!version.matches(&Version::new(1, 2, 4)));
+    }
+
+    #[test]
+    fn parse_git_reference() {
+        let id: PackageId = "git:https://example.com/mod.git#main".parse().unwrap();
+
+        assert_eq!(id.registry(), Registry::Git);
+        assert_eq!(id.name(), "https://example.com/mod.git#main");
+        assert!(id.version().is_none());
+
+        let id: PackageId = "git:https://example.com/mod.git#v1@^1".parse().unwrap();
+
+        assert_eq!(id.name(), "https://example.com/mod.git#v1");
+        assert_eq!(id.version(), Some(&VersionReq::parse("^1").unwrap()));
+    }
+
+    #[test]
+    fn parse_at_in_url() {
+        let id: PackageId = "git:git@github.com:locenv/mod.git#main".parse().unwrap();
+
+        assert_eq!(id.name(), "git@github.com:locenv/mod.git#main");
+        assert!(id.version().is_none());
+
+        let id: PackageId = "git:git@github.com:locenv/mod.git@~1.1".parse().unwrap();
+
+        assert_eq!(id.name(), "git@github.com:locenv/mod.git");
+        assert_eq!(id.version(), Some(&VersionReq::parse("~1.1").unwrap()));
+
+        let id: PackageId = "https://user@example.com/mod.zip".parse().unwrap();
+
+        assert_eq!(id.registry(), Registry::Https);
+        assert_eq!(id.name(), "//user@example.com/mod.zip");
+        assert!(id.version().is_none());
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert!(matches!(
+            "locenv/mod".parse::<PackageId>(),
+            Err(IdentifierError::InvalidFormat)
+        ));
+        assert!(matches!(
+            "github:".parse::<PackageId>(),
+            Err(IdentifierError::InvalidFormat)
+        ));
+        assert!(matches!(
+            "github:@1".parse::<PackageId>(),
+            Err(IdentifierError::InvalidFormat)
+        ));
+        assert!(matches!(
+            "svn:locenv/mod".parse::<PackageId>(),
+            Err(IdentifierError::UnknowRegistry)
+        ));
+        assert!(matches!(
+            "github:locenv/mod@latest".parse::<PackageId>(),
+            Err(IdentifierError::InvalidVersion(_))
+        ));
+    }
+
+    #[test]
+    fn relative_path() {
+        let id: PackageId = "path:modules/m1@1".parse().unwrap();
+        let relative = id.relative_to(Path::new("/project"));
+
+        assert_eq!(relative.name(), "/project/modules/m1");
+        assert_eq!(relative.version(), id.version());
+
+        let id: PackageId = "github:locenv/mod".parse().unwrap();
+
+        assert_eq!(id.relative_to(Path::new("/project")).name(), "locenv/mod");
+    }
+
+    #[test]
+    fn constrained_version() {
+        let id: PackageId = "github:locenv/mod@^1".parse().unwrap();
+        let other = VersionReq::parse(">=1.2").unwrap();
+        let version = id.constrained([&other]).version().cloned().unwrap();
+
+        assert!(version.matches(&Version::new(1, 3, 0)));
+        assert!(!version.matches(&Version::new(1, 1, 0)));
+        assert!(!version.matches(&Version::new(2, 0, 0)));
+
+        let id: PackageId = "github:locenv/mod".parse().unwrap();
+
+        assert!(id.constrained([]).version().is_none());
+    }
+}
@@ -0,0 +1,59 @@
+use crate::package::{Package, Registry};
+use crate::{git, gitea, github, gitlab, https, local};
+use semver::VersionReq;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+/// Represents a place where the packages can be downloaded.
+pub trait PackageRegistry {
+    /// Download the package `name` that match `version`, or the latest one if `version` is [`None`].
+    fn get_package(
+        &self,
+        name: &str,
+        version: Option<&VersionReq>,
+    ) -> Result<Package, GetPackageError>;
+
+    /// Check if the installed module should be updated even if its version is the same as the
+    /// package.
+    fn always_update(&self) -> bool {
+        false
+    }
+}
+
+/// Gets the implementation of `registry`.
+pub fn open(registry: Registry) -> Box<dyn PackageRegistry> {
+    match registry {
+        Registry::GitHub => Box::new(github::GitHub),
+        Registry::GitLab => Box::new(gitlab::GitLab::from_env()),
+        Registry::Gitea => Box::new(gitea::Gitea::from_env()),
+        Registry::Git => Box::new(git::Git),
+        Registry::Path => Box::new(local::Local),
+        Registry::Https => Box::new(https::Https),
+    }
+}
+
+#[derive(Debug)]
+pub enum GetPackageError {
+    InvalidIdentifier,
+    NoMatchingRelease(VersionReq),
+    Failed(Box<dyn Error>),
+}
+
+impl Error for GetPackageError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Failed(e) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl Display for GetPackageError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Self::InvalidIdentifier => f.write_str("invalid package identifier"),
+            Self::NoMatchingRelease(v) => write!(f, "no release matches version {}", v),
+            Self::Failed(e) => e.fmt(f),
+        }
+    }
+}
@@ -0,0 +1,426 @@
+use crate::package::{Package, PackageContent};
+use crate::registry::GetPackageError;
+use semver::{Version, VersionReq};
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::{Read, Seek};
+
+/// Name of the release asset that can be used on all platforms.
+const UNIVERSAL_ASSET: &str = "locenv-module.zip";
+
+/// Maximum number of pages to look for the release that match the version constraint.
+const MAX_RELEASE_PAGES: u32 = 10;
+
+#[cfg(target_os = "linux")]
+const PLATFORM: &str = "linux";
+
+#[cfg(target_os = "macos")]
+const PLATFORM: &str = "darwin";
+
+#[cfg(target_os = "windows")]
+const PLATFORM: &str = "win32";
+
+#[cfg(target_arch = "aarch64")]
+const CPU: &str = "aarch64";
+
+#[cfg(target_arch = "x86_64")]
+const CPU: &str = "amd64";
+
+/// Gets the name of the release asset for the current platform.
+fn platform_asset() -> String {
+    format!("locenv-module-{}-{}.zip", PLATFORM, CPU)
+}
+
+/// Represents the releases of a repository on the hosting service (e.g. GitHub). The implementation
+/// only need to call the API of the service, the rest is done by [`get_package()`].
+pub trait ReleaseHost {
+    type Release;
+    type Asset;
+    type Error: Into<GetPackageError>;
+
+    /// Maximum number of releases on each page of [`ReleaseHost::list_releases()`].
+    const PER_PAGE: usize;
+
+    /// List the releases on `page`, which is started from 1. The newest release come first.
+    fn list_releases(&self, page: u32) -> Result<Vec<Self::Release>, Self::Error>;
+
+    /// Download `asset` into a temporary file.
+    fn download(&self, asset: &Self::Asset) -> Result<File, Self::Error>;
+
+    fn tag_name(release: &Self::Release) -> &str;
+
+    /// Check if `release` is published (e.g. not a draft).
+    fn is_published(release: &Self::Release) -> bool;
+
+    fn assets(release: &Self::Release) -> &[Self::Asset];
+
+    fn asset_name(asset: &Self::Asset) -> &str;
+
+    /// Gets the latest release. The default implementation use the first published release on the
+    /// first page of [`ReleaseHost::list_releases()`].
+    fn latest_release(&self) -> Result<Self::Release, GetPackageError> {
+        self.list_releases(1)
+            .map_err(Into::into)?
+            .into_iter()
+            .find(Self::is_published)
+            .ok_or_else(|| Error::NoRelease.into())
+    }
+}
+
+/// Download the package from the release with the highest version that match `version`, or the
+/// latest release if `version` is [`None`]. The checksum (`<asset>.sha256`) and the signature
+/// (`<asset>.minisig`) are downloaded too if the release has it.
+pub fn get_package<H: ReleaseHost>(
+    host: &H,
+    version: Option<&VersionReq>,
+) -> Result<Package, GetPackageError> {
+    // Get release.
+    let release = match version {
+        Some(v) => find_release(host, v)?,
+        None => host.latest_release()?,
+    };
+
+    // Download release asset.
+    let assets = H::assets(&release);
+    let asset = select_asset(assets, H::asset_name)
+        .ok_or_else(|| Error::NoMatchingAsset(H::tag_name(&release).into(), platform_asset()))?;
+    let mut content = host.download(asset).map_err(Into::into)?;
+
+    content.rewind().map_err(Error::ReadFailed)?;
+
+    // Download the files to verify the package.
+    let name = H::asset_name(asset);
+    let checksum = download_data(host, assets, &format!("{}.sha256", name))?;
+    let signature = download_data(host, assets, &format!("{}.minisig", name))?;
+
+    Ok(Package {
+        content: PackageContent::Archive(content),
+        checksum,
+        signature,
+    })
+}
+
+/// Find the release with the highest version that match `version`.
+fn find_release<H: ReleaseHost>(
+    host: &H,
+    version: &VersionReq,
+) -> Result<H::Release, GetPackageError> {
+    let mut found: Option<(Version, H::Release)> = None;
+
+    for page in 1..=MAX_RELEASE_PAGES {
+        let releases = host.list_releases(page).map_err(Into::into)?;
+        let last = releases.len() < H::PER_PAGE;
+
+        for release in releases {
+            if !H::is_published(&release) {
+                continue;
+            }
+
+            let tag = H::tag_name(&release).to_owned();
+
+            keep_highest(&mut found, version, &tag, release);
+        }
+
+        if last {
+            break;
+        }
+    }
+
+    match found {
+        Some((_, r)) => Ok(r),
+        None => Err(GetPackageError::NoMatchingRelease(version.clone())),
+    }
+}
+
+/// Download the asset `name` in `assets` into the memory. Returns [`None`] if there is no such asset.
+fn download_data<H: ReleaseHost>(
+    host: &H,
+    assets: &[H::Asset],
+    name: &str,
+) -> Result<Option<Vec<u8>>, GetPackageError> {
+    let asset = match assets.iter().find(|a| H::asset_name(a) == name) {
+        Some(v) => v,
+        None => return Ok(None),
+    };
+
+    let mut file = host.download(asset).map_err(Into::into)?;
+    let mut data = Vec::new();
+
+    file.rewind().map_err(Error::ReadFailed)?;
+    file.read_to_end(&mut data).map_err(Error::ReadFailed)?;
+
+    Ok(Some(data))
+}
+
+/// Gets the version of the release from its tag name with an optional `v` prefix.
+fn tag_version(tag: &str) -> Option<Version> {
+    Version::parse(tag.strip_prefix('v').unwrap_or(tag)).ok()
+}
+
+/// Keep the release in `found` that has the highest version that match `version`.
+pub fn keep_highest<R>(
+    found: &mut Option<(Version, R)>,
+    version: &VersionReq,
+    tag: &str,
+    release: R,
+) {
+    let v = match tag_version(tag) {
+        Some(r) => r,
+        None => return,
+    };
+
+    if !version.matches(&v) {
+        return;
+    }
+
+    if found.as_ref().map(|(f, _)| v > *f).unwrap_or(true) {
+        *found = Some((v, release));
+    }
+}
+
+/// Select the asset for the current platform from `assets`. The asset named
+/// `locenv-module-<platform>-<cpu>.zip` is preferred over `locenv-module.zip`. The only asset of the
+/// release is used if it does not follow the naming convention. Returns [`None`] if there is no
+/// suitable asset.
+fn select_asset<A, F>(assets: &[A], name: F) -> Option<&A>
+where
+    F: Fn(&A) -> &str,
+{
+    let specific = platform_asset();
+
+    for wanted in [specific.as_str(), UNIVERSAL_ASSET] {
+        if let Some(asset) = assets.iter().find(|a| name(a) == wanted) {
+            return Some(asset);
+        }
+    }
+
+    // Fall back to the only asset for the release that was published before the naming convention.
+    match assets {
+        [asset] if !name(asset).starts_with("locenv-module") => Some(asset),
+        _ => None,
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    NoRelease,
+    NoMatchingAsset(String, String),
+    ReadFailed(std::io::Error),
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::ReadFailed(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Self::NoRelease => f.write_str("the repository does not have any release"),
+            Self::NoMatchingAsset(r, n) => write!(
+                f,
+                "release {} does not contain {} or {}",
+                r, n, UNIVERSAL_ASSET
+            ),
+            Self::ReadFailed(e) => write!(f, "read downloaded asset failed: {}", e),
+        }
+    }
+}
+
+impl From<Error> for GetPackageError {
+    fn from(e: Error) -> Self {
+        Self::Failed(Box::new(e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn highest_matching_release() {
+        let version = VersionReq::parse("^1.1").unwrap();
+        let mut found: Option<(Version, &str)> = None;
+
+        for tag in ["v1.0.0", "1.3.0", "v2.0.0", "v1.2.0", "latest", "v1.1.0"] {
+            keep_highest(&mut found, &version, tag, tag);
+        }
+
+        assert_eq!(found, Some((Version::new(1, 3, 0), "1.3.0")));
+    }
+
+    #[test]
+    fn no_matching_release() {
+        let version = VersionReq::parse("^3").unwrap();
+        let mut found: Option<(Version, &str)> = None;
+
+        for tag in ["v1.0.0", "v2.0.0", "nightly"] {
+            keep_highest(&mut found, &version, tag, tag);
+        }
+
+        assert!(found.is_none());
+    }
+
+    #[test]
+    fn platform_asset_preferred() {
+        let specific = platform_asset();
+        let assets = [UNIVERSAL_ASSET, specific.as_str(), "checksums.txt"];
+
+        assert_eq!(select_asset(&assets, |a| a), Some(&specific.as_str()));
+    }
+
+    #[test]
+    fn universal_asset() {
+        let assets = [
+            "locenv-module-unknown-unknown.zip",
+            UNIVERSAL_ASSET,
+            "locenv-module.zip.sha256",
+        ];
+
+        assert_eq!(select_asset(&assets, |a| a), Some(&UNIVERSAL_ASSET));
+    }
+
+    #[test]
+    fn only_asset() {
+        assert_eq!(select_asset(&["module.zip"], |a| a), Some(&"module.zip"));
+        assert_eq!(select_asset(&["a.zip", "b.zip"], |a| a), None);
+        assert_eq!(
+            select_asset(&["locenv-module-unknown-unknown.zip"], |a| a),
+            None
+        );
+        assert_eq!(select_asset::<&str, _>(&[], |a| a), None);
+    }
+
+    #[test]
+    fn package_from_matching_release() {
+        let host = FakeHost {
+            pages: vec![
+                vec![
+                    FakeRelease::new("v1.3.0", false, &[UNIVERSAL_ASSET]),
+                    FakeRelease::new(
+                        "v1.2.0",
+                        true,
+                        &[UNIVERSAL_ASSET, "locenv-module.zip.sha256"],
+                    ),
+                ],
+                vec![FakeRelease::new("v1.1.0", true, &[UNIVERSAL_ASSET])],
+            ],
+        };
+
+        let version = VersionReq::parse("^1.1").unwrap();
+        let mut package = get_package(&host, Some(&version)).unwrap();
+        let mut content = String::new();
+
+        match &mut package.content {
+            PackageContent::Archive(f) => f.read_to_string(&mut content).unwrap(),
+            PackageContent::Directory(_) => panic!("the package is not an archive"),
+        };
+
+        assert_eq!(content, "v1.2.0/locenv-module.zip");
+        assert_eq!(
+            package.checksum.as_deref(),
+            Some(&b"v1.2.0/locenv-module.zip.sha256"[..])
+        );
+        assert!(package.signature.is_none());
+    }
+
+    #[test]
+    fn package_from_latest_release() {
+        let host = FakeHost {
+            pages: vec![vec![
+                FakeRelease::new("v2.0.0", false, &[UNIVERSAL_ASSET]),
+                FakeRelease::new("v1.0.0", true, &[UNIVERSAL_ASSET]),
+            ]],
+        };
+
+        let package = get_package(&host, None).unwrap();
+
+        assert!(package.checksum.is_none());
+
+        let host = FakeHost { pages: Vec::new() };
+
+        assert!(matches!(
+            get_package(&host, None),
+            Err(GetPackageError::Failed(_))
+        ));
+    }
+
+    #[test]
+    fn no_release_matches() {
+        let host = FakeHost {
+            pages: vec![vec![FakeRelease::new("v1.0.0", true, &[UNIVERSAL_ASSET])]],
+        };
+        let version = VersionReq::parse("^2").unwrap();
+
+        assert!(matches!(
+            get_package(&host, Some(&version)),
+            Err(GetPackageError::NoMatchingRelease(v)) if v == version
+        ));
+    }
+
+    struct FakeHost {
+        pages: Vec<Vec<FakeRelease>>,
+    }
+
+    #[derive(Clone)]
+    struct FakeRelease {
+        tag: String,
+        published: bool,
+        assets: Vec<String>,
+    }
+
+    impl FakeRelease {
+        fn new(tag: &str, published: bool, assets: &[&str]) -> Self {
+            Self {
+                tag: tag.into(),
+                published,
+                assets: assets.iter().map(|a| format!("{}/{}", tag, a)).collect(),
+            }
+        }
+    }
+
+    impl ReleaseHost for FakeHost {
+        type Release = FakeRelease;
+        type Asset = String;
+        type Error = GetPackageError;
+
+        const PER_PAGE: usize = 2;
+
+        fn list_releases(&self, page: u32) -> Result<Vec<FakeRelease>, GetPackageError> {
+            Ok(self
+                .pages
+                .get(page as usize - 1)
+                .cloned()
+                .unwrap_or_default())
+        }
+
+        fn download(&self, asset: &String) -> Result<File, GetPackageError> {
+            let mut file = tempfile::tempfile().unwrap();
+
+            file.write_all(asset.as_bytes()).unwrap();
+
+            Ok(file)
+        }
+
+        fn tag_name(release: &FakeRelease) -> &str {
+            &release.tag
+        }
+
+        fn is_published(release: &FakeRelease) -> bool {
+            release.published
+        }
+
+        fn assets(release: &FakeRelease) -> &[String] {
+            &release.assets
+        }
+
+        fn asset_name(asset: &String) -> &str {
+            asset.split_once('/').unwrap().1
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use crate::package::{Package, PackageContent};
+use context::Context;
+use minisign_verify::{PublicKey, Signature};
+use sha2::{Digest, Sha256};
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::io::{Read, Seek};
+use std::path::PathBuf;
+
+/// Verify the checksum of `package`. The signature is also required if there are trusted keys in
+/// `LOCENV_DATA`. Do nothing if the context is insecure or the package is not an archive (e.g. from a
+/// Git repository).
+pub fn verify(context: &Context, package: &mut Package) -> Result<(), VerifyError> {
+    let file = match &mut package.content {
+        PackageContent::Archive(f) => f,
+        PackageContent::Directory(_) => return Ok(()),
+    };
+
+    if context.insecure() {
+        return Ok(());
+    }
+
+    // Read package.
+    let mut content = Vec::new();
+
+    file.read_to_end(&mut content)
+        .map_err(VerifyError::ReadFailed)?;
+    file.rewind().map_err(VerifyError::ReadFailed)?;
+
+    // Verify checksum. The file is in the format of sha256sum so we only need the first field. The
+    // checksum is always required otherwise the package is not verified at all when there are no
+    // trusted keys. The release that was published without it need --insecure to install.
+    let checksum = package.checksum.as_ref().ok_or(VerifyError::NoChecksum)?;
+    let expected = std::str::from_utf8(checksum)
+        .ok()
+        .and_then(|v| v.split_whitespace().next())
+        .ok_or(VerifyError::InvalidChecksum)?
+        .to_lowercase();
+    let actual = format!("{:x}", Sha256::digest(&content));
+
+    if expected != actual {
+        return Err(VerifyError::ChecksumMismatch(expected, actual));
+    }
+
+    // Verify signature.
+    let keys = load_keys(context)?;
+
+    if keys.is_empty() {
+        return Ok(());
+    }
+
+    let signature = package.signature.as_ref().ok_or(VerifyError::NoSignature)?;
+    let signature = std::str::from_utf8(signature)
+        .ok()
+        .and_then(|v| Signature::decode(v).ok())
+        .ok_or(VerifyError::InvalidSignature)?;
+
+    if keys
+        .iter()
+        .any(|k| k.verify(&content, &signature, false).is_ok())
+    {
+        Ok(())
+    } else {
+        Err(VerifyError::UntrustedSignature)
+    }
+}
+
+/// Load all `*.pub` files in the trusted keys directory.
+fn load_keys(context: &Context) -> Result<Vec<PublicKey>, VerifyError> {
+    let path = context.data().trusted_keys(false).unwrap().path();
+    let entries = match std::fs::read_dir(&path) {
+        Ok(r) => r,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(VerifyError::LoadKeyFailed(path, e.to_string())),
+    };
+
+    let mut keys = Vec::new();
+
+    for entry in entries {
+        let path = entry
+            .map_err(|e| VerifyError::LoadKeyFailed(path.clone(), e.to_string()))?
+            .path();
+
+        if path.extension().map(|e| e != "pub").unwrap_or(true) {
+            continue;
+        }
+
+        match PublicKey::from_file(&path) {
+            Ok(r) => keys.push(r),
+            Err(e) => return Err(VerifyError::LoadKeyFailed(path, e.to_string())),
+        }
+    }
+
+    Ok(keys)
+}
+
+#[derive(Debug)]
+pub enum VerifyError {
+    ReadFailed(std::io::Error),
+    NoChecksum,
+    InvalidChecksum,
+    ChecksumMismatch(String, String),
+    LoadKeyFailed(PathBuf, String),
+    NoSignature,
+    InvalidSignature,
+    UntrustedSignature,
+}
+
+impl Error for VerifyError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::ReadFailed(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl Display for VerifyError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Self::ReadFailed(e) => write!(f, "Failed to read the package: {}", e),
+            Self::NoChecksum => f.write_str("The package does not have a SHA-256 checksum"),
+            Self::InvalidChecksum => {
+                f.write_str("The SHA-256 checksum of the package is not valid")
+            }
+            Self::ChecksumMismatch(e, a) => write!(
+                f,
+                "SHA-256 checksum of the package is {}, expected {}",
+                a, e
+            ),
+            Self::LoadKeyFailed(p, e) => write!(f, "Failed to load {}: {}", p.display(), e),
+            Self::NoSignature => f.write_str("The package is not signed"),
+            Self::InvalidSignature => f.write_str("The signature of the package is not valid"),
+            Self::UntrustedSignature => {
+                f.write_str("The package is not signed by any of the trusted keys")
+            }
+        }
+    }
+}
@@ -1,33 +1,154 @@
+use module::PackageId;
+use schemars::gen::SchemaGenerator;
+use schemars::schema::{InstanceType, Schema, SchemaObject};
+use schemars::JsonSchema;
 use serde::de::Visitor;
 use serde::{Deserialize, Deserializer};
-use std::collections::HashMap;
+use source::LoadError;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fmt::{Display, Formatter, Write};
+use std::path::Path;
 use url::Url;
 
+pub mod lock;
 pub mod repository;
+pub mod source;
+pub mod validation;
 
+/// Represents `locenv-services.yml`. The schema also contains `include`, which is handled by the loader
+/// before deserializing into this type.
 #[derive(Deserialize)]
 pub struct ApplicationConfiguration {
     pub configurations: HashMap<String, ServiceConfigurations>,
     pub instances: HashMap<String, InstanceConfigurations>,
+
+    /// Modules that required by the project, keyed by the name of the module.
+    #[serde(default)]
+    pub modules: BTreeMap<String, PackageId>,
 }
 
-#[derive(Deserialize)]
+impl ApplicationConfiguration {
+    /// Load the configuration from `path` together with all included files and the override file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, LoadError> {
+        let mut value = serde_yaml::Value::Null;
+
+        for file in source::load(path.as_ref())? {
+            Self::merge(&mut value, file.value);
+        }
+
+        serde_yaml::from_value(value).map_err(LoadError::InvalidConfiguration)
+    }
+
+    /// Gets the names of configurations that used by the instances enabled with `profiles`.
+    pub fn required_configurations(&self, profiles: &[&str]) -> HashSet<&str> {
+        self.instances
+            .values()
+            .filter(|i| i.is_enabled(profiles))
+            .map(|i| i.configuration.as_str())
+            .collect()
+    }
+
+    /// Gets all profiles that declared by the instances.
+    pub fn profiles(&self) -> BTreeSet<&str> {
+        self.instances
+            .values()
+            .flat_map(|i| i.profiles.iter().map(|p| p.as_str()))
+            .collect()
+    }
+
+    /// Merge `other` into `base`. Mappings are merged recursively, `null` removes the key and any other
+    /// values are replaced.
+    pub fn merge(base: &mut serde_yaml::Value, other: serde_yaml::Value) {
+        match (base, other) {
+            (serde_yaml::Value::Mapping(base), serde_yaml::Value::Mapping(other)) => {
+                for (key, value) in other {
+                    if value.is_null() {
+                        base.remove(&key);
+                    } else if let Some(v) = base.get_mut(&key) {
+                        Self::merge(v, value);
+                    } else {
+                        base.insert(key, value);
+                    }
+                }
+            }
+            (base, other) => *base = other,
+        }
+    }
+}
+
+impl JsonSchema for ApplicationConfiguration {
+    fn schema_name() -> String {
+        "ApplicationConfiguration".into()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        let mut schema = SchemaObject {
+            instance_type: Some(InstanceType::Object.into()),
+            ..Default::default()
+        };
+
+        // The included files, which can be a string or a list of it.
+        let mut include = SchemaObject::default();
+
+        include.metadata().description = Some("Other files to merge into this file, relative to this file. A single file can be specified as a string.".into());
+        include.subschemas().any_of = Some(vec![
+            gen.subschema_for::<String>(),
+            gen.subschema_for::<Vec<String>>(),
+        ]);
+
+        let mut modules = gen
+            .subschema_for::<BTreeMap<String, PackageId>>()
+            .into_object();
+
+        modules.metadata().description =
+            Some("Modules that required by the project, keyed by the name of the module.".into());
+
+        let object = schema.object();
+
+        object.properties.insert("include".into(), include.into());
+        object.properties.insert(
+            "configurations".into(),
+            gen.subschema_for::<HashMap<String, ServiceConfigurations>>(),
+        );
+        object.properties.insert(
+            "instances".into(),
+            gen.subschema_for::<HashMap<String, InstanceConfigurations>>(),
+        );
+        object.properties.insert("modules".into(), modules.into());
+        object.required.insert("configurations".into());
+        object.required.insert("instances".into());
+
+        schema.into()
+    }
+}
+
+#[derive(Deserialize, JsonSchema)]
 pub struct ServiceConfigurations {
     pub repository: RepositoryConfigurations,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, JsonSchema)]
 pub struct InstanceConfigurations {
     pub configuration: String,
+
+    /// Profiles to enable this instance. The instance without any profiles is always enabled.
+    #[serde(default)]
+    pub profiles: Vec<String>,
 }
 
-#[derive(Deserialize)]
+impl InstanceConfigurations {
+    pub fn is_enabled(&self, profiles: &[&str]) -> bool {
+        self.profiles.is_empty() || self.profiles.iter().any(|p| profiles.contains(&p.as_str()))
+    }
+}
+
+#[derive(Deserialize, JsonSchema)]
 pub struct RepositoryConfigurations {
     pub uri: RepositoryUri,
     pub r#type: RepositoryType,
 
     #[serde(flatten)]
+    #[schemars(with = "HashMap<String, serde_json::Value>")]
     pub options: HashMap<String, serde_yaml::Value>,
 }
 
@@ -38,6 +159,32 @@ pub enum RepositoryUri {
     Url(Url),
 }
 
+impl JsonSchema for RepositoryUri {
+    fn schema_name() -> String {
+        "RepositoryUri".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        let mut schema = SchemaObject {
+            instance_type: Some(InstanceType::String.into()),
+            ..Default::default()
+        };
+
+        schema.metadata().description =
+            Some("A URL, SCP-syntax (e.g. git@github.com:owner/repo.git) or a path.".into());
+        schema.into()
+    }
+}
+
+impl Display for RepositoryUri {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Self::Scp(v) => v.fmt(f),
+            Self::Url(v) => v.fmt(f),
+        }
+    }
+}
+
 /// Represents a URL for Secure copy protocol (SCP).
 #[derive(Debug)]
 pub struct ScpUrl {
@@ -136,18 +283,24 @@ impl<'de> Visitor<'de> for ScpUrlVisitor {
 }
 
 /// Type of repository.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum RepositoryType {
     Git,
+    Local,
+    Archive,
 }
 
 /// Represents a set of configuration to define how to interact with the service like how to build, etc.
-#[derive(Deserialize)]
+#[derive(Deserialize, JsonSchema)]
 pub struct ServiceDefinition {
     pub linux: Option<PlatformConfigurations>,
     pub darwin: Option<PlatformConfigurations>,
     pub win32: Option<PlatformConfigurations>,
+
+    /// Modules that required by the build scripts, keyed by the name of the module.
+    #[serde(default)]
+    pub modules: BTreeMap<String, PackageId>,
 }
 
 impl ServiceDefinition {
@@ -176,7 +329,7 @@ impl ServiceDefinition {
 }
 
 /// Represents a platform-specific configuration for a service.
-#[derive(Clone, Deserialize)]
+#[derive(Clone, Deserialize, JsonSchema)]
 pub struct PlatformConfigurations {
     pub build: Option<String>,
 }
@@ -214,3 +367,67 @@ impl AsRef<str> for Platform {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_yaml::Value;
+
+    #[test]
+    fn merge_mappings() {
+        let mut base: Value = serde_yaml::from_str("a: {b: 1, c: 2}\nd: [1, 2]\ne: 3").unwrap();
+        let other: Value = serde_yaml::from_str("a: {c: 4, f: 5}\nd: [3]\ne: ~\ng: 6").unwrap();
+        let expected: Value = serde_yaml::from_str("a: {b: 1, c: 4, f: 5}\nd: [3]\ng: 6").unwrap();
+
+        ApplicationConfiguration::merge(&mut base, other);
+
+        assert_eq!(base, expected);
+    }
+
+    #[test]
+    fn merge_replace() {
+        let mut base: Value = serde_yaml::from_str("a: {b: 1}").unwrap();
+        let other: Value = serde_yaml::from_str("a: abc").unwrap();
+
+        ApplicationConfiguration::merge(&mut base, other.clone());
+
+        assert_eq!(base, other);
+
+        let mut base = Value::Null;
+
+        ApplicationConfiguration::merge(&mut base, other.clone());
+
+        assert_eq!(base, other);
+    }
+
+    #[test]
+    fn load_with_override() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("locenv-services.yml");
+        let common = "configurations:
+  a:
+    repository: {uri: ./a, type: local}
+  b:
+    repository: {uri: ./b, type: local}
+";
+
+        std::fs::write(dir.path().join("common.yml"), common).unwrap();
+        std::fs::write(
+            &path,
+            "include: common.yml\ninstances:\n  a: {configuration: a}\n  b: {configuration: b}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("locenv-services.override.yml"),
+            "instances:\n  a: {profiles: [dev]}\n  b: ~\n",
+        )
+        .unwrap();
+
+        let config = ApplicationConfiguration::load(&path).unwrap();
+
+        assert_eq!(config.configurations.len(), 2);
+        assert_eq!(config.instances.len(), 1);
+        assert_eq!(config.instances["a"].configuration, "a");
+        assert_eq!(config.instances["a"].profiles, ["dev"]);
+    }
+}
@@ -0,0 +1,175 @@
+use crate::RepositoryConfigurations;
+use module::PackageId;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::ErrorKind;
+use std::path::Path;
+
+/// Represents `locenv-services.lock`, which record the revision of each configuration so all
+/// developers get the same services.
+#[derive(Default, Deserialize, PartialEq, Serialize)]
+pub struct Lockfile {
+    #[serde(default)]
+    pub configurations: BTreeMap<String, LockedConfiguration>,
+
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub modules: BTreeMap<String, LockedModule>,
+}
+
+#[derive(Clone, Deserialize, PartialEq, Serialize)]
+pub struct LockedConfiguration {
+    pub uri: String,
+    pub revision: String,
+}
+
+#[derive(Clone, Deserialize, PartialEq, Serialize)]
+pub struct LockedModule {
+    pub id: String,
+    pub version: String,
+}
+
+impl Lockfile {
+    /// Load the lockfile from `path`. Return an empty lockfile if `path` does not exist.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, yaml::FileError> {
+        match yaml::load_file(path) {
+            Ok(r) => Ok(r),
+            Err(yaml::FileError::OpenFailed(e)) if e.kind() == ErrorKind::NotFound => {
+                Ok(Self::default())
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        let data = serde_yaml::to_string(self).unwrap();
+
+        std::fs::write(path, data)
+    }
+
+    /// Gets the locked revision for the configuration `name`. Return `None` if the configuration is
+    /// not locked or the repository has been changed since it was locked.
+    pub fn revision(&self, name: &str, config: &RepositoryConfigurations) -> Option<&str> {
+        let locked = self.configurations.get(name)?;
+
+        if locked.uri == config.uri.to_string() {
+            Some(&locked.revision)
+        } else {
+            None
+        }
+    }
+
+    /// Gets the locked version for the module `name`. Return `None` if the module is not locked or
+    /// its identifier has been changed since it was locked.
+    pub fn module_version(&self, name: &str, id: &PackageId) -> Option<&str> {
+        let locked = self.modules.get(name)?;
+
+        if locked.id == id.to_string() {
+            Some(&locked.version)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn repository(uri: &str) -> RepositoryConfigurations {
+        serde_yaml::from_str(&format!("{{uri: '{}', type: git}}", uri)).unwrap()
+    }
+
+    fn locked() -> Lockfile {
+        let mut lock = Lockfile::default();
+
+        lock.configurations.insert(
+            "web".into(),
+            LockedConfiguration {
+                uri: "https://example.com/web.git".into(),
+                revision: "0123456789abcdef".into(),
+            },
+        );
+
+        lock.modules.insert(
+            "nodejs".into(),
+            LockedModule {
+                id: "github:locenv/nodejs".into(),
+                version: "1.2.0".into(),
+            },
+        );
+
+        lock
+    }
+
+    #[test]
+    fn load_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        let lock = Lockfile::load(dir.path().join("locenv-services.lock")).unwrap();
+
+        assert!(lock == Lockfile::default());
+    }
+
+    #[test]
+    fn save_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("locenv-services.lock");
+        let lock = locked();
+
+        lock.save(&path).unwrap();
+
+        assert!(Lockfile::load(&path).unwrap() == lock);
+    }
+
+    #[test]
+    fn save_without_modules() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("locenv-services.lock");
+        let mut lock = locked();
+
+        lock.modules.clear();
+        lock.save(&path).unwrap();
+
+        assert!(!std::fs::read_to_string(&path).unwrap().contains("modules"));
+        assert!(Lockfile::load(&path).unwrap() == lock);
+    }
+
+    #[test]
+    fn load_invalid() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("locenv-services.lock");
+
+        std::fs::write(&path, "configurations: [").unwrap();
+
+        assert!(Lockfile::load(&path).is_err());
+    }
+
+    #[test]
+    fn locked_revision() {
+        let lock = locked();
+
+        assert_eq!(
+            lock.revision("web", &repository("https://example.com/web.git")),
+            Some("0123456789abcdef")
+        );
+        assert_eq!(
+            lock.revision("web", &repository("https://example.com/other.git")),
+            None
+        );
+        assert_eq!(
+            lock.revision("api", &repository("https://example.com/web.git")),
+            None
+        );
+    }
+
+    #[test]
+    fn locked_module_version() {
+        let lock = locked();
+        let id = PackageId::from_str("github:locenv/nodejs").unwrap();
+        let other = PackageId::from_str("github:someone/nodejs").unwrap();
+
+        assert_eq!(lock.module_version("nodejs", &id), Some("1.2.0"));
+        assert_eq!(lock.module_version("nodejs", &other), None);
+        assert_eq!(lock.module_version("python", &id), None);
+    }
+}
@@ -0,0 +1,682 @@
+use crate::RepositoryUri;
+use flate2::read::GzDecoder;
+use http::{Method, StatusCode};
+use kuro::{Endpoint, Headers, StatusLine};
+use kuro_macros::{kuro, FollowLocation, NoDefaultHeaders};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::fs::{create_dir_all, File};
+use std::io::{Read, Seek, Write};
+use std::path::{Component, Path, PathBuf};
+use tar::Archive;
+use xz2::read::XzDecoder;
+use zip::ZipArchive;
+
+/// Name of the file inside the destination to keep track where the content came from.
+const STATE_FILE: &str = ".locenv-archive";
+
+/// Download the archive from `uri` and extract it to `dest`. `revision` is the expected checksum if
+/// `sha256` option is not specified.
+pub fn download<P: AsRef<Path>, D: AsRef<Path>>(
+    uri: &RepositoryUri,
+    project: P,
+    dest: D,
+    options: &HashMap<String, serde_yaml::Value>,
+    revision: Option<&str>,
+) -> Result<(), ArchiveError> {
+    let dest = dest.as_ref();
+    let format = get_format(uri, options)?;
+    let checksum = get_checksum(options)?.or_else(|| revision.map(|v| v.to_lowercase()));
+    let strip = get_strip_components(options)?;
+
+    // Get the archive.
+    let (mut file, source) = open(uri, project.as_ref())?;
+
+    // Verify checksum.
+    let actual = compute_checksum(&mut file).map_err(ArchiveError::ReadFailed)?;
+
+    if let Some(expected) = checksum {
+        if expected != actual {
+            return Err(ArchiveError::ChecksumMismatch(expected, actual));
+        }
+    }
+
+    // Extract.
+    create_dir_all(dest).map_err(ArchiveError::ExtractFailed)?;
+
+    match format {
+        ArchiveFormat::TarGz => extract_tar(GzDecoder::new(file), dest, strip),
+        ArchiveFormat::TarXz => extract_tar(XzDecoder::new(file), dest, strip),
+        ArchiveFormat::Zip => extract_zip(file, dest, strip),
+    }
+    .map_err(ArchiveError::ExtractFailed)?;
+
+    // Write state.
+    let state = ArchiveState {
+        uri: source,
+        sha256: actual,
+    };
+
+    let state = serde_yaml::to_string(&state).unwrap();
+
+    std::fs::write(dest.join(STATE_FILE), state).map_err(ArchiveError::ExtractFailed)
+}
+
+/// Download the archive again if the URI or the checksum has been changed. See [`download`] for
+/// `revision`.
+pub fn update<P: AsRef<Path>, D: AsRef<Path>>(
+    uri: &RepositoryUri,
+    project: P,
+    dest: D,
+    options: &HashMap<String, serde_yaml::Value>,
+    revision: Option<&str>,
+) -> Result<(), ArchiveError> {
+    let dest = dest.as_ref();
+    let checksum = get_checksum(options)?.or_else(|| revision.map(|v| v.to_lowercase()));
+
+    // Check if the content already up to date. We treat unreadable state as an outdated one.
+    if let Ok(state) = yaml::load_file::<_, ArchiveState>(dest.join(STATE_FILE)) {
+        let changed = match &checksum {
+            Some(v) => v != &state.sha256,
+            None => false,
+        };
+
+        if !changed && state.uri == uri.to_string() {
+            return Ok(());
+        }
+    }
+
+    // Extract the new content next to the current one so the current one is still usable if failed.
+    let parent = dest.parent().unwrap();
+    let temp = tempfile::Builder::new()
+        .prefix(".locenv-")
+        .tempdir_in(parent)
+        .map_err(ArchiveError::ExtractFailed)?;
+
+    download(uri, project, temp.path(), options, revision)?;
+
+    // Replace the content.
+    std::fs::remove_dir_all(dest).map_err(ArchiveError::ExtractFailed)?;
+    std::fs::rename(temp.path(), dest).map_err(ArchiveError::ExtractFailed)
+}
+
+/// Options that supported by archive repository.
+pub const OPTIONS: &[&str] = &["format", "sha256", "strip-components"];
+
+/// Gets all problems of `uri` and `options`.
+pub fn validate(
+    uri: &RepositoryUri,
+    options: &HashMap<String, serde_yaml::Value>,
+) -> Vec<ArchiveError> {
+    let mut errors = Vec::new();
+
+    if let RepositoryUri::Url(v) = uri {
+        if !matches!(v.scheme(), "http" | "https" | "file") {
+            errors.push(ArchiveError::InvalidUri);
+        }
+    }
+
+    if let Err(e) = get_format(uri, options) {
+        errors.push(e);
+    }
+
+    if let Err(e) = get_checksum(options) {
+        errors.push(e);
+    }
+
+    if let Err(e) = get_strip_components(options) {
+        errors.push(e);
+    }
+
+    errors
+}
+
+/// Gets the checksum of the archive that was extracted to `dest`.
+pub fn revision<D: AsRef<Path>>(dest: D) -> Option<String> {
+    let state: ArchiveState = yaml::load_file(dest.as_ref().join(STATE_FILE)).ok()?;
+
+    Some(state.sha256)
+}
+
+/// Check if the archive can be opened without accessing the network.
+pub fn is_local(uri: &RepositoryUri) -> bool {
+    match uri {
+        RepositoryUri::Scp(_) => true,
+        RepositoryUri::Url(v) => v.scheme() == "file",
+    }
+}
+
+#[derive(Debug)]
+pub enum ArchiveError {
+    InvalidUri,
+    InvalidOption(&'static str),
+    UnknownFormat,
+    OpenFailed(std::io::Error),
+    DownloadFailed(DownloadArchiveError),
+    ReadFailed(std::io::Error),
+    ChecksumMismatch(String, String),
+    ExtractFailed(std::io::Error),
+}
+
+#[derive(Deserialize, Serialize)]
+struct ArchiveState {
+    uri: String,
+    sha256: String,
+}
+
+enum ArchiveFormat {
+    TarGz,
+    TarXz,
+    Zip,
+}
+
+fn open(uri: &RepositoryUri, project: &Path) -> Result<(File, String), ArchiveError> {
+    let path = match uri {
+        RepositoryUri::Scp(v) => project.join(v.to_string()),
+        RepositoryUri::Url(v) => match v.scheme() {
+            "http" | "https" => {
+                let mut file = kuro::execute(DownloadArchive::new(v.as_str()))
+                    .map_err(ArchiveError::DownloadFailed)?;
+
+                file.rewind().map_err(ArchiveError::ReadFailed)?;
+
+                return Ok((file, v.to_string()));
+            }
+            "file" => v.to_file_path().map_err(|_| ArchiveError::InvalidUri)?,
+            _ => return Err(ArchiveError::InvalidUri),
+        },
+    };
+
+    let file = File::open(&path).map_err(ArchiveError::OpenFailed)?;
+
+    Ok((file, uri.to_string()))
+}
+
+fn compute_checksum(file: &mut File) -> std::io::Result<String> {
+    let mut hasher = Sha256::new();
+
+    std::io::copy(file, &mut hasher)?;
+    file.rewind()?;
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+fn extract_tar<R: Read>(data: R, dest: &Path, strip: usize) -> std::io::Result<()> {
+    let mut archive = Archive::new(data);
+    let root = dest.canonicalize()?;
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let relative = match strip_path(&entry.path()?, strip) {
+            Some(v) => v,
+            None => continue,
+        };
+        let path = dest.join(&relative);
+
+        // Make sure the entry will not be written outside the destination via a symbolic link that
+        // was extracted before.
+        if !is_inside(&root, &path)? {
+            return Err(outside_error(&relative));
+        }
+
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent)?;
+        }
+
+        // Replace the existing file so we will not write through it if it is a symbolic link.
+        if let Ok(m) = path.symlink_metadata() {
+            if !m.is_dir() {
+                std::fs::remove_file(&path)?;
+            }
+        }
+
+        let kind = entry.header().entry_type();
+
+        if kind.is_hard_link() {
+            // Tar does not strip the components of the hard link target for us.
+            let target = match entry.link_name()? {
+                Some(v) => match strip_path(&v, strip) {
+                    Some(v) => dest.join(v),
+                    None => continue,
+                },
+                None => continue,
+            };
+
+            if !is_inside(&root, &target)? {
+                return Err(outside_error(&relative));
+            }
+
+            std::fs::hard_link(target, path)?;
+        } else {
+            if kind.is_symlink() {
+                let target = entry.link_name()?.unwrap_or_default();
+
+                if !is_link_inside(&relative, &target) {
+                    return Err(outside_error(&relative));
+                }
+            }
+
+            entry.unpack(path)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Check if `path` is inside `root` after resolving the symbolic links on its existing ancestors.
+/// `root` must be a canonical path.
+fn is_inside(root: &Path, path: &Path) -> std::io::Result<bool> {
+    let mut current = path.parent();
+
+    while let Some(dir) = current {
+        match dir.canonicalize() {
+            Ok(v) => return Ok(v.starts_with(root)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => current = dir.parent(),
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(false)
+}
+
+/// Check if the symbolic link at `link` (relative to the destination) that pointed to `target` is
+/// not pointing outside the destination.
+fn is_link_inside(link: &Path, target: &Path) -> bool {
+    let mut depth: usize = link.components().count() - 1;
+
+    for c in target.components() {
+        match c {
+            Component::Normal(_) => depth += 1,
+            Component::CurDir => {}
+            Component::ParentDir => match depth.checked_sub(1) {
+                Some(v) => depth = v,
+                None => return false,
+            },
+            Component::RootDir | Component::Prefix(_) => return false,
+        }
+    }
+
+    true
+}
+
+fn outside_error(path: &Path) -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        format!("{} is pointing outside the destination", path.display()),
+    )
+}
+
+fn extract_zip(data: File, dest: &Path, strip: usize) -> std::io::Result<()> {
+    let mut archive = ZipArchive::new(data)?;
+
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        let path = match entry.enclosed_name() {
+            Some(v) => match strip_path(v, strip) {
+                Some(v) => dest.join(v),
+                None => continue,
+            },
+            None => continue,
+        };
+
+        if entry.is_dir() {
+            create_dir_all(&path)?;
+            continue;
+        }
+
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent)?;
+        }
+
+        let mut file = File::create(&path)?;
+
+        std::io::copy(&mut entry, &mut file)?;
+
+        #[cfg(target_family = "unix")]
+        if let Some(mode) = entry.unix_mode() {
+            use std::os::unix::fs::PermissionsExt;
+
+            file.set_permissions(std::fs::Permissions::from_mode(mode))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Remove `count` leading components from `path`. Return `None` if nothing left or the path is
+/// pointing outside the destination.
+fn strip_path(path: &Path, count: usize) -> Option<PathBuf> {
+    let mut result = PathBuf::new();
+    let mut skipped = 0;
+
+    for c in path.components() {
+        match c {
+            Component::Normal(v) => {
+                if skipped < count {
+                    skipped += 1;
+                } else {
+                    result.push(v);
+                }
+            }
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+
+    if result.as_os_str().is_empty() {
+        None
+    } else {
+        Some(result)
+    }
+}
+
+fn get_format(
+    uri: &RepositoryUri,
+    options: &HashMap<String, serde_yaml::Value>,
+) -> Result<ArchiveFormat, ArchiveError> {
+    let key = "format";
+
+    // Use the explicit format as-is.
+    match options.get(key) {
+        Some(serde_yaml::Value::String(v)) => {
+            return match v.to_lowercase().as_str() {
+                "tar.gz" | "tgz" => Ok(ArchiveFormat::TarGz),
+                "tar.xz" | "txz" => Ok(ArchiveFormat::TarXz),
+                "zip" => Ok(ArchiveFormat::Zip),
+                _ => Err(ArchiveError::InvalidOption(key)),
+            }
+        }
+        Some(_) => return Err(ArchiveError::InvalidOption(key)),
+        None => {}
+    }
+
+    // Determine from the file extension.
+    let name = match uri {
+        RepositoryUri::Scp(v) => Cow::Owned(v.to_string()),
+        RepositoryUri::Url(v) => Cow::Borrowed(v.path()),
+    };
+
+    let name = name.to_lowercase();
+
+    if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        Ok(ArchiveFormat::TarGz)
+    } else if name.ends_with(".tar.xz") || name.ends_with(".txz") {
+        Ok(ArchiveFormat::TarXz)
+    } else if name.ends_with(".zip") {
+        Ok(ArchiveFormat::Zip)
+    } else {
+        Err(ArchiveError::UnknownFormat)
+    }
+}
+
+fn get_checksum(
+    options: &HashMap<String, serde_yaml::Value>,
+) -> Result<Option<String>, ArchiveError> {
+    let key = "sha256";
+    let value = match options.get(key) {
+        Some(serde_yaml::Value::String(v)) => v,
+        Some(_) => return Err(ArchiveError::InvalidOption(key)),
+        None => return Ok(None),
+    };
+
+    if value.len() != 64 || !value.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(ArchiveError::InvalidOption(key));
+    }
+
+    Ok(Some(value.to_lowercase()))
+}
+
+fn get_strip_components(
+    options: &HashMap<String, serde_yaml::Value>,
+) -> Result<usize, ArchiveError> {
+    let key = "strip-components";
+
+    match options.get(key) {
+        Some(v) => match v.as_u64() {
+            Some(v) => Ok(v as usize),
+            None => Err(ArchiveError::InvalidOption(key)),
+        },
+        None => Ok(0),
+    }
+}
+
+/// Download an archive over HTTP.
+#[derive(FollowLocation, NoDefaultHeaders)]
+#[kuro(error = "DownloadArchiveError")]
+struct DownloadArchive<'url> {
+    url: &'url str,
+    file: File,
+}
+
+impl<'url> DownloadArchive<'url> {
+    fn new(url: &'url str) -> Self {
+        Self {
+            url,
+            file: tempfile::tempfile().unwrap(),
+        }
+    }
+}
+
+impl<'url> Endpoint for DownloadArchive<'url> {
+    type Output = File;
+
+    fn method(&self) -> &Method {
+        &Method::GET
+    }
+
+    fn url(&self) -> Cow<'_, str> {
+        self.url.into()
+    }
+
+    fn override_request_headers<'a>(&'a self, h: &mut Headers<'a>) {
+        h.user_agent = Some("locenv");
+    }
+
+    fn process_response_status(&mut self, line: &StatusLine) -> Result<(), Self::Err> {
+        let c = line.code();
+
+        if c == StatusCode::OK {
+            Ok(())
+        } else {
+            Err(DownloadArchiveError::UnexpectedStatusCode(c))
+        }
+    }
+
+    fn process_response_body(&mut self, chunk: &[u8]) -> Result<(), Self::Err> {
+        self.file
+            .write_all(chunk)
+            .map_err(DownloadArchiveError::WriteFailed)
+    }
+
+    fn new_invalid_response_header(&self, line: &[u8]) -> Self::Err {
+        DownloadArchiveError::InvalidResponseHeader(line.into())
+    }
+
+    fn new_http_stack_error(&self, cause: curl::Error) -> Self::Err {
+        DownloadArchiveError::HttpStackFailed(cause)
+    }
+
+    fn build_output(self, _: StatusLine) -> Result<Self::Output, Self::Err> {
+        Ok(self.file)
+    }
+}
+
+#[derive(Debug)]
+pub enum DownloadArchiveError {
+    HttpStackFailed(curl::Error),
+    InvalidResponseHeader(Vec<u8>),
+    UnexpectedStatusCode(StatusCode),
+    WriteFailed(std::io::Error),
+}
+
+impl Error for DownloadArchiveError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::HttpStackFailed(e) => Some(e),
+            Self::WriteFailed(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl Display for DownloadArchiveError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Self::HttpStackFailed(e) => write!(f, "HTTP stack failed: {}", e),
+            Self::InvalidResponseHeader(h) => write!(f, "{:?} is not a valid header", h),
+            Self::UnexpectedStatusCode(c) => write!(f, "unexpected status {}", c),
+            Self::WriteFailed(e) => write!(f, "write failed: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tar::{Builder, EntryType, Header};
+
+    #[test]
+    fn strip_path_components() {
+        assert_eq!(
+            strip_path(Path::new("project-1.0/src/main.rs"), 1),
+            Some(PathBuf::from("src/main.rs"))
+        );
+        assert_eq!(
+            strip_path(Path::new("./project-1.0/README.md"), 1),
+            Some(PathBuf::from("README.md"))
+        );
+        assert_eq!(
+            strip_path(Path::new("README.md"), 0),
+            Some(PathBuf::from("README.md"))
+        );
+        assert_eq!(strip_path(Path::new("project-1.0/"), 1), None);
+        assert_eq!(
+            strip_path(Path::new("project-1.0/../../etc/passwd"), 1),
+            None
+        );
+        assert_eq!(strip_path(Path::new("../etc/passwd"), 0), None);
+        assert_eq!(strip_path(Path::new("/etc/passwd"), 0), None);
+    }
+
+    #[test]
+    fn link_inside() {
+        assert!(is_link_inside(Path::new("a/link"), Path::new("b")));
+        assert!(is_link_inside(Path::new("a/link"), Path::new("../b")));
+        assert!(is_link_inside(Path::new("a/link"), Path::new("./b/../c")));
+        assert!(!is_link_inside(Path::new("a/link"), Path::new("../../b")));
+        assert!(!is_link_inside(Path::new("link"), Path::new("..")));
+        assert!(!is_link_inside(
+            Path::new("a/link"),
+            Path::new("b/../../../c")
+        ));
+        assert!(!is_link_inside(
+            Path::new("a/link"),
+            Path::new("/etc/passwd")
+        ));
+    }
+
+    #[test]
+    fn inside_destination() {
+        let dest = tempfile::tempdir().unwrap();
+        let root = dest.path().canonicalize().unwrap();
+
+        assert!(is_inside(&root, &root.join("a/b/c")).unwrap());
+        assert!(!is_inside(&root, &root.join("../c")).unwrap());
+    }
+
+    #[cfg(target_family = "unix")]
+    #[test]
+    fn inside_destination_via_symlink() {
+        let dest = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        let root = dest.path().canonicalize().unwrap();
+
+        std::os::unix::fs::symlink(outside.path(), root.join("link")).unwrap();
+
+        assert!(!is_inside(&root, &root.join("link/file")).unwrap());
+    }
+
+    #[test]
+    fn extract_tar_entries() {
+        let dest = tempfile::tempdir().unwrap();
+        let mut builder = Builder::new(Vec::new());
+
+        append_file(&mut builder, "project-1.0/a/file", b"abc");
+        append_link(&mut builder, "project-1.0/a/link", "file");
+
+        extract_tar(builder.into_inner().unwrap().as_slice(), dest.path(), 1).unwrap();
+
+        assert_eq!(std::fs::read(dest.path().join("a/file")).unwrap(), b"abc");
+        assert_eq!(std::fs::read(dest.path().join("a/link")).unwrap(), b"abc");
+    }
+
+    #[test]
+    fn extract_tar_link_outside() {
+        let dest = tempfile::tempdir().unwrap();
+        let mut builder = Builder::new(Vec::new());
+
+        append_link(&mut builder, "a/link", "../../outside");
+
+        let error = extract_tar(builder.into_inner().unwrap().as_slice(), dest.path(), 0)
+            .err()
+            .unwrap();
+
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        assert!(dest.path().join("a/link").symlink_metadata().is_err());
+    }
+
+    #[test]
+    fn extract_tar_absolute_link() {
+        let dest = tempfile::tempdir().unwrap();
+        let mut builder = Builder::new(Vec::new());
+
+        append_link(&mut builder, "link", "/etc");
+
+        let error = extract_tar(builder.into_inner().unwrap().as_slice(), dest.path(), 0)
+            .err()
+            .unwrap();
+
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn extract_tar_parent_entry() {
+        let dest = tempfile::tempdir().unwrap();
+        let parent = dest.path().join("dest");
+        let mut builder = Builder::new(Vec::new());
+        let mut header = Header::new_gnu();
+        let name = b"../escaped";
+
+        // The builder does not allow ".." so we need to write the name directly.
+        header.as_gnu_mut().unwrap().name[..name.len()].copy_from_slice(name);
+        header.set_size(3);
+        header.set_cksum();
+        builder.append(&header, &b"abc"[..]).unwrap();
+
+        create_dir_all(&parent).unwrap();
+        extract_tar(builder.into_inner().unwrap().as_slice(), &parent, 0).unwrap();
+
+        assert!(!dest.path().join("escaped").exists());
+    }
+
+    fn append_file(builder: &mut Builder<Vec<u8>>, path: &str, data: &[u8]) {
+        let mut header = Header::new_gnu();
+
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append_data(&mut header, path, data).unwrap();
+    }
+
+    fn append_link(builder: &mut Builder<Vec<u8>>, path: &str, target: &str) {
+        let mut header = Header::new_gnu();
+
+        header.set_entry_type(EntryType::Symlink);
+        header.set_size(0);
+        builder.append_link(&mut header, path, target).unwrap();
+    }
+}
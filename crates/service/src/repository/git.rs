@@ -1,14 +1,38 @@
+use super::TransferProgress;
 use crate::RepositoryUri;
-use git2::build::RepoBuilder;
-use git2::{Cred, FetchOptions, RemoteCallbacks, Repository};
+use git2::build::{CheckoutBuilder, RepoBuilder};
+use git2::{
+    Commit, Cred, FetchOptions, FetchPrune, ObjectType, Oid, RemoteCallbacks, Repository,
+    ResetType, TreeWalkMode, TreeWalkResult,
+};
 use std::borrow::Cow;
 use std::collections::HashMap;
+use std::fs::{create_dir_all, File, OpenOptions};
 use std::path::Path;
 
-pub fn clone<D: AsRef<Path>>(
+/// The first line of a Git LFS pointer file.
+const LFS_POINTER_PREFIX: &[u8] = b"version https://git-lfs.github.com/spec/v1";
+
+/// https://github.com/git-lfs/git-lfs/blob/main/docs/spec.md
+const LFS_POINTER_MAX_SIZE: usize = 1024;
+
+/// https://git-scm.com/book/en/v2/Git-Internals-The-Refspec
+const ALL_BRANCHES: &str = "+refs/heads/*:refs/remotes/origin/*";
+
+/// Value of `depth` to fetch the whole history of a shallow clone (`GIT_FETCH_DEPTH_UNSHALLOW`).
+const UNSHALLOW: i32 = i32::MAX;
+
+/// Clone `uri` to `dest`. The objects will be fetched into the bare repository on `cache` first then
+/// clone from it unless `depth` option is specified. The cache will be used as-is if `offline` is
+/// `true`. The current branch will be moved to `revision` if it is specified.
+pub fn clone<D: AsRef<Path>, C: AsRef<Path>>(
     uri: &RepositoryUri,
     dest: D,
+    cache: C,
     options: &HashMap<String, serde_yaml::Value>,
+    revision: Option<&str>,
+    offline: bool,
+    progress: &mut dyn FnMut(&TransferProgress),
 ) -> Result<(), CloneError> {
     let mut repo = RepoBuilder::new();
 
@@ -29,45 +53,135 @@ pub fn clone<D: AsRef<Path>>(
         },
     };
 
-    // Get remote URL.
-    let url: Cow<str> = match uri {
-        RepositoryUri::Scp(url) => {
-            let mut options = FetchOptions::new();
-            let mut callbacks = RemoteCallbacks::new();
+    // Specify which files to checkout.
+    let paths = get_sparse_paths(options).map_err(CloneError::InvalidOption)?;
+    let submodules = get_submodules(options).map_err(CloneError::InvalidOption)?;
 
-            // Setup credential callback.
-            callbacks.credentials(|_, username, _| {
-                let mut private = dirs::home_dir().unwrap();
+    if let Some(paths) = &paths {
+        let mut checkout = CheckoutBuilder::new();
 
-                private.push(".ssh");
-                private.push("id_rsa");
+        for path in paths {
+            checkout.path(path);
+        }
 
-                Cred::ssh_key(username.unwrap(), None, &private, None)
-            });
+        repo.with_checkout(checkout);
+    }
+
+    // Setup fetch options. The cache is a full clone so we cannot use it with a limited depth.
+    let depth = get_depth(options).map_err(CloneError::InvalidOption)?;
+    let mut fetch = FetchOptions::new();
+    let url: Cow<str> = match depth {
+        Some(depth) => {
+            fetch.depth(depth);
+            fetch.remote_callbacks(remote_callbacks(uri, progress));
+
+            match uri {
+                RepositoryUri::Scp(url) => Cow::Owned(url.to_string()),
+                RepositoryUri::Url(url) => Cow::Borrowed(url.as_str()),
+            }
+        }
+        None => {
+            let mut callbacks = RemoteCallbacks::new();
 
-            options.remote_callbacks(callbacks);
+            if !offline {
+                let _lock = lock_cache(cache.as_ref()).map_err(CloneError::CacheLockFailed)?;
 
-            repo.fetch_options(options);
+                update_cache(uri, cache.as_ref(), progress)
+                    .map_err(CloneError::CacheUpdateFailed)?;
+            }
 
-            Cow::Owned(url.to_string())
+            callbacks.transfer_progress(|p| report_progress(&p, progress));
+            fetch.remote_callbacks(callbacks);
+
+            cache.as_ref().to_string_lossy()
         }
-        RepositoryUri::Url(url) => Cow::Borrowed(url.as_str()),
     };
 
-    // Clone.
-    let repo = repo
-        .clone(url.as_ref(), dest.as_ref())
-        .map_err(|e| CloneError::CloneFailed(e))?;
+    repo.fetch_options(fetch);
+
+    // Clone. The builder need to be dropped here since its callbacks borrow the progress.
+    let repo = {
+        let mut builder = repo;
+
+        builder
+            .clone(url.as_ref(), dest.as_ref())
+            .map_err(|e| CloneError::CloneFailed(e))?
+    };
+
+    // Point origin back to the upstream so the user can work with the clone as usual.
+    if depth.is_none() {
+        repo.remote_set_url("origin", &uri.to_string())
+            .map_err(CloneError::CloneFailed)?;
+    }
+
+    if let Some(revision) = revision {
+        // The shallow clone may not contains the revision.
+        if let Some(depth) = depth {
+            if find_commit(&repo, revision).is_none() {
+                fetch_revision(&repo, uri, depth, revision, progress)
+                    .map_err(CloneError::CloneFailed)?;
+            }
+        }
+
+        let commit = find_commit(&repo, revision)
+            .ok_or_else(|| CloneError::RevisionNotFound(revision.into()))?;
+
+        reset(&repo, &commit, paths.as_deref());
+    }
+
+    update_submodules(&repo, &submodules, paths.as_deref())
+        .map_err(CloneError::SubmoduleUpdateFailed)?;
+
+    // We don't have Git LFS client so we need to make sure the service does not depend on it.
+    let pointers = find_lfs_pointers(&repo, paths.as_deref());
+
+    if !pointers.is_empty() {
+        return Err(CloneError::LfsNotSupported(pointers));
+    }
 
     repo.cleanup_state().unwrap();
 
     Ok(())
 }
 
+/// Options that supported by Git repository.
+pub const OPTIONS: &[&str] = &["branch", "depth", "sparse-paths", "submodules"];
+
+/// Gets the name of all invalid options.
+pub fn validate(options: &HashMap<String, serde_yaml::Value>) -> Vec<&'static str> {
+    let mut invalid = Vec::new();
+
+    if let Err(GetBranchToCloneError::InvalidValue(key)) = get_branch_to_clone(options) {
+        invalid.push(key);
+    }
+
+    if let Err(key) = get_depth(options) {
+        invalid.push(key);
+    }
+
+    if let Err(key) = get_sparse_paths(options) {
+        invalid.push(key);
+    }
+
+    if let Err(key) = get_submodules(options) {
+        invalid.push(key);
+    }
+
+    invalid
+}
+
+/// Check if `clone` can be done without accessing the network.
+pub fn is_cached<C: AsRef<Path>>(cache: C, options: &HashMap<String, serde_yaml::Value>) -> bool {
+    matches!(get_depth(options), Ok(None)) && cache.as_ref().exists()
+}
+
 // https://github.com/rust-lang/git2-rs/blob/master/examples/pull.rs
-pub fn pull<P: AsRef<Path>>(
+pub fn pull<P: AsRef<Path>, C: AsRef<Path>>(
+    uri: &RepositoryUri,
     path: P,
+    cache: C,
     options: &HashMap<String, serde_yaml::Value>,
+    progress: &mut dyn FnMut(&TransferProgress),
 ) -> Result<(), PullError> {
     let repo = Repository::open(path).map_err(|e| PullError::RepositoryOpenFailed(e))?;
 
@@ -91,33 +205,45 @@ pub fn pull<P: AsRef<Path>>(
         },
     }
 
-    // Fetch origin.
-    let mut remote = repo
-        .find_remote("origin")
-        .map_err(|e| PullError::FindOriginFailed(e))?;
+    let depth = get_depth(options).map_err(PullError::InvalidOption)?;
+    let paths = get_sparse_paths(options).map_err(PullError::InvalidOption)?;
+    let submodules = get_submodules(options).map_err(PullError::InvalidOption)?;
 
-    remote
-        .fetch(&[] as &[String], None, None)
-        .map_err(|e| PullError::FetchOriginFailed(e))?;
+    // Fetch only the current branch.
+    let branch: String = local.shorthand().unwrap().into();
+    let tracking = format!("refs/remotes/origin/{}", branch);
+    let spec = format!("+refs/heads/{}:{}", branch, tracking);
 
-    // Find a commit on the remote to merge.
-    let reference = repo.find_reference("FETCH_HEAD").unwrap();
+    fetch(&repo, uri, cache.as_ref(), depth, &spec, progress)?;
+
+    // Find a commit on the remote to merge. We don't use FETCH_HEAD since it may contains the other
+    // branches.
+    let reference = repo
+        .find_reference(&tracking)
+        .map_err(PullError::FetchOriginFailed)?;
     let latest = repo.reference_to_annotated_commit(&reference).unwrap();
 
     drop(reference);
 
-    // Merge.
+    // Merge. The history of a shallow clone may not be connected with the fetched commit so we always
+    // move the branch to the fetched commit for it.
     let (ma, _) = repo.merge_analysis(&[&latest]).unwrap();
 
-    if ma.is_fast_forward() {
+    if depth.is_some() || ma.is_fast_forward() {
         let name: String = local.name().unwrap().into();
         let id = latest.id();
         let msg = format!("Fast-Forward: Setting {} to id: {}", name, id);
-        let mut options = git2::build::CheckoutBuilder::default();
+        let mut options = CheckoutBuilder::default();
 
         options.force();
         options.remove_ignored(true);
 
+        if let Some(paths) = &paths {
+            for path in paths {
+                options.path(path);
+            }
+        }
+
         local.set_target(id, &msg).unwrap();
         repo.set_head(&name).unwrap();
         repo.checkout_head(Some(&mut options)).unwrap();
@@ -126,16 +252,94 @@ pub fn pull<P: AsRef<Path>>(
         panic!("Cannot fast-forward for some reason")
     }
 
+    // Submodules need to be updated even if the repository is already up to date since the option
+    // may have just been enabled.
+    update_submodules(&repo, &submodules, paths.as_deref())
+        .map_err(PullError::SubmoduleUpdateFailed)?;
+
+    let pointers = find_lfs_pointers(&repo, paths.as_deref());
+
+    if !pointers.is_empty() {
+        return Err(PullError::LfsNotSupported(pointers));
+    }
+
     // Clean up.
     repo.cleanup_state().unwrap();
 
     Ok(())
 }
 
+/// Move the current branch of the repository on `path` to `revision`. The upstream will be fetched
+/// if `revision` does not exist on the repository unless `offline` is `true`.
+pub fn checkout<P: AsRef<Path>, C: AsRef<Path>>(
+    uri: &RepositoryUri,
+    path: P,
+    cache: C,
+    options: &HashMap<String, serde_yaml::Value>,
+    revision: &str,
+    offline: bool,
+    progress: &mut dyn FnMut(&TransferProgress),
+) -> Result<(), PullError> {
+    let repo = Repository::open(path).map_err(PullError::RepositoryOpenFailed)?;
+    let depth = get_depth(options).map_err(PullError::InvalidOption)?;
+    let paths = get_sparse_paths(options).map_err(PullError::InvalidOption)?;
+    let submodules = get_submodules(options).map_err(PullError::InvalidOption)?;
+
+    // Fetch the revision if we don't have it. The repository will be left untouched if the revision
+    // still cannot be found.
+    if find_commit(&repo, revision).is_none() && !offline {
+        match depth {
+            Some(depth) => fetch_revision(&repo, uri, depth, revision, progress)
+                .map_err(PullError::FetchOriginFailed)?,
+            None => fetch(&repo, uri, cache.as_ref(), None, ALL_BRANCHES, progress)?,
+        }
+    }
+
+    let commit =
+        find_commit(&repo, revision).ok_or_else(|| PullError::RevisionNotFound(revision.into()))?;
+
+    reset(&repo, &commit, paths.as_deref());
+    drop(commit);
+
+    update_submodules(&repo, &submodules, paths.as_deref())
+        .map_err(PullError::SubmoduleUpdateFailed)?;
+
+    let pointers = find_lfs_pointers(&repo, paths.as_deref());
+
+    if !pointers.is_empty() {
+        return Err(PullError::LfsNotSupported(pointers));
+    }
+
+    repo.cleanup_state().unwrap();
+
+    Ok(())
+}
+
+/// Gets the commit ID of HEAD on the repository on `path`.
+pub fn revision<P: AsRef<Path>>(path: P) -> Option<String> {
+    let repo = Repository::open(path).ok()?;
+    let commit = repo.head().ok()?.peel_to_commit().ok()?;
+
+    Some(commit.id().to_string())
+}
+
+/// Check if the repository on `path` contains `revision`.
+pub fn has_revision<P: AsRef<Path>>(path: P, revision: &str) -> bool {
+    match Repository::open(path) {
+        Ok(repo) => find_commit(&repo, revision).is_some(),
+        Err(_) => false,
+    }
+}
+
 #[derive(Debug)]
 pub enum CloneError {
     InvalidOption(&'static str),
     CloneFailed(git2::Error),
+    CacheLockFailed(std::io::Error),
+    CacheUpdateFailed(git2::Error),
+    RevisionNotFound(String),
+    SubmoduleUpdateFailed(git2::Error),
+    LfsNotSupported(Vec<String>),
 }
 
 #[derive(Debug)]
@@ -144,6 +348,283 @@ pub enum PullError {
     FindOriginFailed(git2::Error),
     FetchOriginFailed(git2::Error),
     InvalidOption(&'static str),
+    CacheLockFailed(std::io::Error),
+    CacheUpdateFailed(git2::Error),
+    RevisionNotFound(String),
+    SubmoduleUpdateFailed(git2::Error),
+    LfsNotSupported(Vec<String>),
+}
+
+/// Fetch `spec` from the upstream via the cache. The upstream will be fetched directly if `depth` is
+/// specified since the cache cannot be used in that case.
+fn fetch(
+    repo: &Repository,
+    uri: &RepositoryUri,
+    cache: &Path,
+    depth: Option<i32>,
+    spec: &str,
+    progress: &mut dyn FnMut(&TransferProgress),
+) -> Result<(), PullError> {
+    let mut fetch = FetchOptions::new();
+
+    // We need to specify the depth on every fetch otherwise the clone will not be shallow anymore.
+    match depth {
+        Some(depth) => {
+            let mut remote = repo
+                .find_remote("origin")
+                .map_err(|e| PullError::FindOriginFailed(e))?;
+
+            fetch.depth(depth);
+            fetch.remote_callbacks(remote_callbacks(uri, progress));
+
+            remote
+                .fetch(&[spec], Some(&mut fetch), None)
+                .map_err(|e| PullError::FetchOriginFailed(e))?;
+        }
+        None => {
+            let mut callbacks = RemoteCallbacks::new();
+
+            let lock = lock_cache(cache).map_err(PullError::CacheLockFailed)?;
+
+            update_cache(uri, cache, progress).map_err(PullError::CacheUpdateFailed)?;
+            drop(lock);
+
+            let mut remote = repo
+                .remote_anonymous(&cache.to_string_lossy())
+                .map_err(PullError::FetchOriginFailed)?;
+
+            callbacks.transfer_progress(|p| report_progress(&p, progress));
+            fetch.remote_callbacks(callbacks);
+
+            remote
+                .fetch(&[spec], Some(&mut fetch), None)
+                .map_err(PullError::FetchOriginFailed)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Fetch `revision` into the shallow clone `repo`. The whole history will be fetched if the
+/// upstream does not allow to fetch the commit directly or the commit is not reachable within
+/// `depth`.
+fn fetch_revision(
+    repo: &Repository,
+    uri: &RepositoryUri,
+    depth: i32,
+    revision: &str,
+    progress: &mut dyn FnMut(&TransferProgress),
+) -> Result<(), git2::Error> {
+    let mut remote = repo.find_remote("origin")?;
+
+    // Try the commit itself first. Some servers do not allow it so we ignore the error.
+    let fetched = {
+        let mut fetch = FetchOptions::new();
+
+        fetch.depth(depth);
+        fetch.remote_callbacks(remote_callbacks(uri, progress));
+
+        remote.fetch(&[revision], Some(&mut fetch), None).is_ok()
+    };
+
+    if fetched && find_commit(repo, revision).is_some() {
+        return Ok(());
+    }
+
+    // Unshallow.
+    let mut fetch = FetchOptions::new();
+
+    fetch.depth(UNSHALLOW);
+    fetch.remote_callbacks(remote_callbacks(uri, progress));
+
+    remote.fetch(
+        &[ALL_BRANCHES, "+refs/tags/*:refs/tags/*"],
+        Some(&mut fetch),
+        None,
+    )
+}
+
+fn find_commit<'repo>(repo: &'repo Repository, revision: &str) -> Option<Commit<'repo>> {
+    let id = Oid::from_str(revision).ok()?;
+
+    repo.find_commit(id).ok()
+}
+
+/// Move the current branch to `commit` and checkout `paths` from it.
+fn reset(repo: &Repository, commit: &Commit, paths: Option<&[&str]>) {
+    let mut options = CheckoutBuilder::new();
+
+    options.force();
+    options.remove_ignored(true);
+
+    if let Some(paths) = paths {
+        for path in paths {
+            options.path(path);
+        }
+    }
+
+    repo.reset(commit.as_object(), ResetType::Hard, Some(&mut options))
+        .unwrap();
+}
+
+/// Create `<cache>.lock` and lock it exclusively so only one process can update the cache at a time,
+/// since the cache is shared between all projects. The lock is released when the returned file is
+/// dropped.
+fn lock_cache(cache: &Path) -> std::io::Result<File> {
+    let path = cache.with_extension("lock");
+
+    if let Some(parent) = path.parent() {
+        create_dir_all(parent)?;
+    }
+
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&path)?;
+
+    file.lock()?;
+
+    Ok(file)
+}
+
+/// Fetch all branches and tags from `uri` into the bare repository on `cache`. The caller must hold
+/// the lock from [`lock_cache()`].
+fn update_cache(
+    uri: &RepositoryUri,
+    cache: &Path,
+    progress: &mut dyn FnMut(&TransferProgress),
+) -> Result<(), git2::Error> {
+    let repo = if cache.exists() {
+        Repository::open_bare(cache)?
+    } else {
+        Repository::init_bare(cache)?
+    };
+
+    // Fetch.
+    let mut remote = repo.remote_anonymous(&uri.to_string())?;
+    let mut fetch = FetchOptions::new();
+
+    fetch.prune(FetchPrune::On);
+    fetch.remote_callbacks(remote_callbacks(uri, progress));
+
+    remote.fetch(
+        &["+refs/heads/*:refs/heads/*", "+refs/tags/*:refs/tags/*"],
+        Some(&mut fetch),
+        None,
+    )?;
+
+    // Point HEAD to the default branch of the upstream so cloning without a branch will get the
+    // same result as cloning from the upstream.
+    let branch = remote.default_branch()?;
+
+    repo.set_head(branch.as_str().unwrap())?;
+
+    Ok(())
+}
+
+fn remote_callbacks<'a>(
+    uri: &RepositoryUri,
+    progress: &'a mut dyn FnMut(&TransferProgress),
+) -> RemoteCallbacks<'a> {
+    let mut callbacks = RemoteCallbacks::new();
+
+    // Setup credential callback.
+    if let RepositoryUri::Scp(_) = uri {
+        callbacks.credentials(|_, username, _| {
+            let mut private = dirs::home_dir().unwrap();
+
+            private.push(".ssh");
+            private.push("id_rsa");
+
+            Cred::ssh_key(username.unwrap(), None, &private, None)
+        });
+    }
+
+    callbacks.transfer_progress(|p| report_progress(&p, progress));
+    callbacks
+}
+
+fn report_progress(progress: &git2::Progress, callback: &mut dyn FnMut(&TransferProgress)) -> bool {
+    callback(&TransferProgress {
+        received_objects: progress.received_objects(),
+        indexed_objects: progress.indexed_objects(),
+        total_objects: progress.total_objects(),
+        received_bytes: progress.received_bytes(),
+    });
+
+    true
+}
+
+/// Initialize and update the submodules that are inside `paths` to the commit recorded in `repo`.
+fn update_submodules(
+    repo: &Repository,
+    mode: &Submodules,
+    paths: Option<&[&str]>,
+) -> Result<(), git2::Error> {
+    if let Submodules::Skip = mode {
+        return Ok(());
+    }
+
+    for mut submodule in repo.submodules()? {
+        if let Some(paths) = paths {
+            if !paths.iter().any(|p| submodule.path().starts_with(p)) {
+                continue;
+            }
+        }
+
+        submodule.update(true, None)?;
+
+        if let Submodules::Recursive = mode {
+            update_submodules(&submodule.open()?, mode, None)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Gets the files inside `paths` on the HEAD of `repo` that is a Git LFS pointer.
+fn find_lfs_pointers(repo: &Repository, paths: Option<&[&str]>) -> Vec<String> {
+    let mut pointers = Vec::new();
+
+    // HEAD will be unborn if the repository is empty.
+    let tree = match repo.head().and_then(|h| h.peel_to_tree()) {
+        Ok(v) => v,
+        Err(_) => return pointers,
+    };
+
+    let odb = repo.odb().unwrap();
+
+    tree.walk(TreeWalkMode::PreOrder, |dir, entry| {
+        if entry.kind() != Some(ObjectType::Blob) {
+            return TreeWalkResult::Ok;
+        }
+
+        let path = format!("{}{}", dir, entry.name().unwrap_or_default());
+
+        if let Some(paths) = paths {
+            if !paths.iter().any(|p| Path::new(&path).starts_with(p)) {
+                return TreeWalkResult::Ok;
+            }
+        }
+
+        // Read only the header first so we don't need to load a large file.
+        match odb.read_header(entry.id()) {
+            Ok((size, _)) if size < LFS_POINTER_MAX_SIZE => {}
+            _ => return TreeWalkResult::Ok,
+        }
+
+        if let Ok(blob) = repo.find_blob(entry.id()) {
+            if blob.content().starts_with(LFS_POINTER_PREFIX) {
+                pointers.push(path);
+            }
+        }
+
+        TreeWalkResult::Ok
+    })
+    .unwrap();
+
+    pointers
 }
 
 fn get_branch_to_clone(
@@ -158,6 +639,73 @@ fn get_branch_to_clone(
     }
 }
 
+/// Gets the number of commits to fetch. `None` mean all commits.
+fn get_depth(options: &HashMap<String, serde_yaml::Value>) -> Result<Option<i32>, &'static str> {
+    let key = "depth";
+    let value = match options.get(key) {
+        Some(v) => v,
+        None => return Ok(None),
+    };
+
+    match value.as_u64() {
+        Some(v) if v > 0 && v <= i32::MAX as u64 => Ok(Some(v as i32)),
+        _ => Err(key),
+    }
+}
+
+/// Gets the paths to checkout from `sparse-paths`. `None` mean all files. This option is checkout-only,
+/// it only limit the files in the working directory and all objects of the fetched commits are still
+/// downloaded.
+fn get_sparse_paths(
+    options: &HashMap<String, serde_yaml::Value>,
+) -> Result<Option<Vec<&str>>, &'static str> {
+    let key = "sparse-paths";
+    let value = match options.get(key) {
+        Some(v) => v,
+        None => return Ok(None),
+    };
+
+    let items = match value {
+        serde_yaml::Value::Sequence(v) if !v.is_empty() => v,
+        _ => return Err(key),
+    };
+
+    let mut paths = Vec::with_capacity(items.len());
+
+    for item in items {
+        match item {
+            serde_yaml::Value::String(v) => paths.push(v.as_str()),
+            _ => return Err(key),
+        }
+    }
+
+    Ok(Some(paths))
+}
+
+/// Gets how to handle the submodules. The value can be `true`, `false` or `recursive`.
+fn get_submodules(
+    options: &HashMap<String, serde_yaml::Value>,
+) -> Result<Submodules, &'static str> {
+    let key = "submodules";
+
+    match options.get(key) {
+        Some(serde_yaml::Value::Bool(v)) => Ok(if *v {
+            Submodules::Update
+        } else {
+            Submodules::Skip
+        }),
+        Some(serde_yaml::Value::String(v)) if v == "recursive" => Ok(Submodules::Recursive),
+        Some(_) => Err(key),
+        None => Ok(Submodules::Skip),
+    }
+}
+
+enum Submodules {
+    Skip,
+    Update,
+    Recursive,
+}
+
 enum GetBranchToCloneError {
     NoOption,
     InvalidValue(&'static str),
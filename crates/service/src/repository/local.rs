@@ -0,0 +1,91 @@
+use crate::RepositoryUri;
+use std::fs::create_dir_all;
+use std::path::{Path, PathBuf};
+
+/// Links `dest` to the directory specified by `uri`, which is relative to `project`. Any existing
+/// link on `dest` that point to a different directory will be replaced.
+pub fn link<P: AsRef<Path>, D: AsRef<Path>>(
+    uri: &RepositoryUri,
+    project: P,
+    dest: D,
+) -> Result<(), LinkError> {
+    let source = resolve(uri, project.as_ref())?;
+    let dest = dest.as_ref();
+
+    // Check if the link already up to date.
+    match std::fs::read_link(dest) {
+        Ok(v) => {
+            if v == source {
+                return Ok(());
+            }
+
+            remove_link(dest).map_err(LinkError::LinkFailed)?;
+        }
+        Err(e) => {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                return Err(LinkError::LinkFailed(e));
+            }
+        }
+    }
+
+    // Create the link.
+    if let Some(parent) = dest.parent() {
+        create_dir_all(parent).map_err(LinkError::LinkFailed)?;
+    }
+
+    create_link(&source, dest).map_err(LinkError::LinkFailed)
+}
+
+/// Check if `uri` can be used as a local repository.
+pub fn validate(uri: &RepositoryUri) -> Result<(), LinkError> {
+    match uri {
+        RepositoryUri::Scp(_) => Ok(()),
+        RepositoryUri::Url(v) => match v.scheme() {
+            "file" => Ok(()),
+            _ => Err(LinkError::InvalidUri),
+        },
+    }
+}
+
+#[derive(Debug)]
+pub enum LinkError {
+    InvalidUri,
+    SourceNotFound(PathBuf),
+    LinkFailed(std::io::Error),
+}
+
+fn resolve(uri: &RepositoryUri, project: &Path) -> Result<PathBuf, LinkError> {
+    // A relative path will be parsed as SCP-syntax without user and path so we need to turn it back.
+    let path = match uri {
+        RepositoryUri::Scp(v) => PathBuf::from(v.to_string()),
+        RepositoryUri::Url(v) => v.to_file_path().map_err(|_| LinkError::InvalidUri)?,
+    };
+
+    let path = project.join(path);
+
+    if !path.is_dir() {
+        return Err(LinkError::SourceNotFound(path));
+    }
+
+    Ok(path)
+}
+
+#[cfg(target_family = "unix")]
+fn create_link(source: &Path, dest: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(source, dest)
+}
+
+#[cfg(target_family = "windows")]
+fn create_link(source: &Path, dest: &Path) -> std::io::Result<()> {
+    std::os::windows::fs::symlink_dir(source, dest)
+}
+
+#[cfg(target_family = "unix")]
+fn remove_link(path: &Path) -> std::io::Result<()> {
+    std::fs::remove_file(path)
+}
+
+#[cfg(target_family = "windows")]
+fn remove_link(path: &Path) -> std::io::Result<()> {
+    std::fs::remove_dir(path)
+}
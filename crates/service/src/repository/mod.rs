@@ -1,40 +1,183 @@
 use super::RepositoryConfigurations;
 use super::RepositoryType;
-use std::path::Path;
+use super::RepositoryUri;
+use context::Context;
+use dirtree::DirectoryError;
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::error::Error;
+use std::path::{Path, PathBuf};
 
+mod archive;
 mod git;
+mod local;
 
-pub fn download<D: AsRef<Path>>(
+/// Download the repository specified by `config` to `destination`. `revision` is the value returned
+/// from [`revision`] to download instead of the latest one. `progress` will be invoked while the
+/// content is being transferred, currently only Git repository reports it.
+pub fn download<D: AsRef<Path>, F: FnMut(&TransferProgress)>(
+    context: &Context,
     config: &RepositoryConfigurations,
     destination: D,
+    revision: Option<&str>,
+    mut progress: F,
 ) -> Result<(), DownloadError> {
+    if context.offline() && !available_offline(context, config) {
+        return Err(DownloadError::Offline);
+    }
+
     let mut guard = UpdateGuard::new(destination.as_ref());
 
     match &config.r#type {
-        RepositoryType::Git => git::clone(&config.uri, destination.as_ref(), &config.options)?,
+        RepositoryType::Git => git::clone(
+            &config.uri,
+            destination.as_ref(),
+            cache(context, &config.uri).map_err(DownloadError::GitCacheUnavailable)?,
+            &config.options,
+            revision,
+            context.offline(),
+            &mut progress,
+        )?,
+        RepositoryType::Local => {
+            local::link(&config.uri, context.project().path(), destination.as_ref())?
+        }
+        RepositoryType::Archive => archive::download(
+            &config.uri,
+            context.project().path(),
+            destination.as_ref(),
+            &config.options,
+            revision,
+        )?,
     }
 
     guard.success = true;
     Ok(())
 }
 
-pub fn update<P: AsRef<Path>>(
+/// Update the repository on `path` to the latest one according to `config`. See [`download`] for
+/// `progress`.
+pub fn update<P: AsRef<Path>, F: FnMut(&TransferProgress)>(
+    context: &Context,
+    config: &RepositoryConfigurations,
+    path: P,
+    mut progress: F,
+) -> Result<(), UpdateError> {
+    update_to(context, config, path.as_ref(), None, &mut progress)
+}
+
+/// Update the repository on `path` to `revision`. See [`download`] for `revision` and `progress`.
+pub fn checkout<P: AsRef<Path>, F: FnMut(&TransferProgress)>(
+    context: &Context,
     config: &RepositoryConfigurations,
     path: P,
+    revision: &str,
+    mut progress: F,
 ) -> Result<(), UpdateError> {
-    let mut guard = UpdateGuard::new(path.as_ref());
+    update_to(
+        context,
+        config,
+        path.as_ref(),
+        Some(revision),
+        &mut progress,
+    )
+}
 
+/// Gets the revision of the repository on `path`. Return `None` if the repository does not have the
+/// revision (e.g. local repository).
+pub fn revision<P: AsRef<Path>>(config: &RepositoryConfigurations, path: P) -> Option<String> {
     match &config.r#type {
-        RepositoryType::Git => git::pull(path.as_ref(), &config.options)?,
+        RepositoryType::Git => git::revision(path),
+        RepositoryType::Local => None,
+        RepositoryType::Archive => archive::revision(path),
     }
+}
 
-    guard.success = true;
-    Ok(())
+/// Check if the repository specified by `config` can be downloaded without accessing the network.
+pub fn available_offline(context: &Context, config: &RepositoryConfigurations) -> bool {
+    match &config.r#type {
+        RepositoryType::Git => match cache(context, &config.uri) {
+            Ok(r) => git::is_cached(r, &config.options),
+            Err(_) => false,
+        },
+        RepositoryType::Local => true,
+        RepositoryType::Archive => archive::is_local(&config.uri),
+    }
+}
+
+/// Check if the repository on `path` can be moved to `revision` without accessing the network.
+pub fn revision_available_offline<P: AsRef<Path>>(
+    config: &RepositoryConfigurations,
+    path: P,
+    revision: &str,
+) -> bool {
+    match &config.r#type {
+        RepositoryType::Git => git::has_revision(path, revision),
+        RepositoryType::Local => true,
+        RepositoryType::Archive => archive::is_local(&config.uri),
+    }
+}
+
+/// Gets the name of options that supported by the repository `type`.
+pub fn options(r#type: &RepositoryType) -> &'static [&'static str] {
+    match r#type {
+        RepositoryType::Git => git::OPTIONS,
+        RepositoryType::Local => &[],
+        RepositoryType::Archive => archive::OPTIONS,
+    }
+}
+
+/// Check `config` without accessing the repository.
+pub fn validate(config: &RepositoryConfigurations) -> Vec<ValidationError> {
+    match &config.r#type {
+        RepositoryType::Git => git::validate(&config.options)
+            .into_iter()
+            .map(ValidationError::InvalidOption)
+            .collect(),
+        RepositoryType::Local => match local::validate(&config.uri) {
+            Ok(_) => Vec::new(),
+            Err(_) => vec![ValidationError::InvalidUri],
+        },
+        RepositoryType::Archive => archive::validate(&config.uri, &config.options)
+            .into_iter()
+            .map(|e| match e {
+                archive::ArchiveError::InvalidOption(key) => ValidationError::InvalidOption(key),
+                archive::ArchiveError::UnknownFormat => ValidationError::ArchiveFormatUnknown,
+                _ => ValidationError::InvalidUri,
+            })
+            .collect(),
+    }
+}
+
+/// Progress of transferring the content of a repository.
+pub struct TransferProgress {
+    pub received_objects: usize,
+    pub indexed_objects: usize,
+    pub total_objects: usize,
+    pub received_bytes: usize,
+}
+
+pub enum ValidationError {
+    InvalidUri,
+    InvalidOption(&'static str),
+    ArchiveFormatUnknown,
 }
 
 pub enum DownloadError {
+    Offline,
+    InvalidUri,
     InvalidOption(&'static str),
     GitCloneFailed(git2::Error),
+    GitCacheUnavailable(std::io::Error),
+    GitCacheFailed(git2::Error),
+    GitRevisionNotFound(String),
+    GitSubmoduleFailed(git2::Error),
+    GitLfsNotSupported(Vec<String>),
+    LocalNotFound(PathBuf),
+    LinkFailed(std::io::Error),
+    ArchiveFormatUnknown,
+    ArchiveDownloadFailed(Box<dyn Error>),
+    ArchiveChecksumMismatch(String, String),
+    ArchiveExtractFailed(std::io::Error),
 }
 
 impl From<git::CloneError> for DownloadError {
@@ -42,15 +185,59 @@ impl From<git::CloneError> for DownloadError {
         match e {
             git::CloneError::InvalidOption(key) => Self::InvalidOption(key),
             git::CloneError::CloneFailed(e) => Self::GitCloneFailed(e),
+            git::CloneError::CacheLockFailed(e) => Self::GitCacheUnavailable(e),
+            git::CloneError::CacheUpdateFailed(e) => Self::GitCacheFailed(e),
+            git::CloneError::RevisionNotFound(r) => Self::GitRevisionNotFound(r),
+            git::CloneError::SubmoduleUpdateFailed(e) => Self::GitSubmoduleFailed(e),
+            git::CloneError::LfsNotSupported(f) => Self::GitLfsNotSupported(f),
+        }
+    }
+}
+
+impl From<local::LinkError> for DownloadError {
+    fn from(e: local::LinkError) -> Self {
+        match e {
+            local::LinkError::InvalidUri => Self::InvalidUri,
+            local::LinkError::SourceNotFound(p) => Self::LocalNotFound(p),
+            local::LinkError::LinkFailed(e) => Self::LinkFailed(e),
+        }
+    }
+}
+
+impl From<archive::ArchiveError> for DownloadError {
+    fn from(e: archive::ArchiveError) -> Self {
+        match e {
+            archive::ArchiveError::InvalidUri => Self::InvalidUri,
+            archive::ArchiveError::InvalidOption(key) => Self::InvalidOption(key),
+            archive::ArchiveError::UnknownFormat => Self::ArchiveFormatUnknown,
+            archive::ArchiveError::OpenFailed(e) => Self::ArchiveDownloadFailed(e.into()),
+            archive::ArchiveError::DownloadFailed(e) => Self::ArchiveDownloadFailed(e.into()),
+            archive::ArchiveError::ReadFailed(e) => Self::ArchiveDownloadFailed(e.into()),
+            archive::ArchiveError::ChecksumMismatch(expected, actual) => {
+                Self::ArchiveChecksumMismatch(expected, actual)
+            }
+            archive::ArchiveError::ExtractFailed(e) => Self::ArchiveExtractFailed(e),
         }
     }
 }
 
 pub enum UpdateError {
+    InvalidUri,
     InvalidOption(&'static str),
     GitOpenFailed(git2::Error),
     GitFindOriginFailed(git2::Error),
     GitFetchOriginFailed(git2::Error),
+    GitCacheUnavailable(std::io::Error),
+    GitCacheFailed(git2::Error),
+    GitRevisionNotFound(String),
+    GitSubmoduleFailed(git2::Error),
+    GitLfsNotSupported(Vec<String>),
+    LocalNotFound(PathBuf),
+    LinkFailed(std::io::Error),
+    ArchiveFormatUnknown,
+    ArchiveDownloadFailed(Box<dyn Error>),
+    ArchiveChecksumMismatch(String, String),
+    ArchiveExtractFailed(std::io::Error),
 }
 
 impl From<git::PullError> for UpdateError {
@@ -60,10 +247,104 @@ impl From<git::PullError> for UpdateError {
             git::PullError::FindOriginFailed(e) => Self::GitFindOriginFailed(e),
             git::PullError::FetchOriginFailed(e) => Self::GitFetchOriginFailed(e),
             git::PullError::InvalidOption(name) => Self::InvalidOption(name),
+            git::PullError::CacheLockFailed(e) => Self::GitCacheUnavailable(e),
+            git::PullError::CacheUpdateFailed(e) => Self::GitCacheFailed(e),
+            git::PullError::RevisionNotFound(r) => Self::GitRevisionNotFound(r),
+            git::PullError::SubmoduleUpdateFailed(e) => Self::GitSubmoduleFailed(e),
+            git::PullError::LfsNotSupported(f) => Self::GitLfsNotSupported(f),
+        }
+    }
+}
+
+impl From<local::LinkError> for UpdateError {
+    fn from(e: local::LinkError) -> Self {
+        match e {
+            local::LinkError::InvalidUri => Self::InvalidUri,
+            local::LinkError::SourceNotFound(p) => Self::LocalNotFound(p),
+            local::LinkError::LinkFailed(e) => Self::LinkFailed(e),
+        }
+    }
+}
+
+impl From<archive::ArchiveError> for UpdateError {
+    fn from(e: archive::ArchiveError) -> Self {
+        match e {
+            archive::ArchiveError::InvalidUri => Self::InvalidUri,
+            archive::ArchiveError::InvalidOption(key) => Self::InvalidOption(key),
+            archive::ArchiveError::UnknownFormat => Self::ArchiveFormatUnknown,
+            archive::ArchiveError::OpenFailed(e) => Self::ArchiveDownloadFailed(e.into()),
+            archive::ArchiveError::DownloadFailed(e) => Self::ArchiveDownloadFailed(e.into()),
+            archive::ArchiveError::ReadFailed(e) => Self::ArchiveDownloadFailed(e.into()),
+            archive::ArchiveError::ChecksumMismatch(expected, actual) => {
+                Self::ArchiveChecksumMismatch(expected, actual)
+            }
+            archive::ArchiveError::ExtractFailed(e) => Self::ArchiveExtractFailed(e),
+        }
+    }
+}
+
+/// Gets the path of the bare repository that shared between all projects for `uri`. The directory
+/// will be created by Git when the repository is initialized.
+fn cache(context: &Context, uri: &RepositoryUri) -> std::io::Result<PathBuf> {
+    let name = format!("{:x}", Sha256::digest(uri.to_string()));
+    let repositories = match context.data().repository(false) {
+        Ok(r) => r,
+        Err(DirectoryError::CreateFailed(e)) => return Err(e),
+    };
+
+    Ok(repositories.by_name(Cow::Owned(name)).path())
+}
+
+fn update_to(
+    context: &Context,
+    config: &RepositoryConfigurations,
+    path: &Path,
+    revision: Option<&str>,
+    progress: &mut dyn FnMut(&TransferProgress),
+) -> Result<(), UpdateError> {
+    // Never remove the existing content here so it is still usable if the update failed. We use
+    // whatever we have in offline mode.
+    match &config.r#type {
+        RepositoryType::Git => match revision {
+            Some(revision) => git::checkout(
+                &config.uri,
+                path,
+                cache(context, &config.uri).map_err(UpdateError::GitCacheUnavailable)?,
+                &config.options,
+                revision,
+                context.offline(),
+                progress,
+            )?,
+            None => {
+                if !context.offline() {
+                    git::pull(
+                        &config.uri,
+                        path,
+                        cache(context, &config.uri).map_err(UpdateError::GitCacheUnavailable)?,
+                        &config.options,
+                        progress,
+                    )?
+                }
+            }
+        },
+        RepositoryType::Local => local::link(&config.uri, context.project().path(), path)?,
+        RepositoryType::Archive => {
+            if !context.offline() || archive::is_local(&config.uri) {
+                archive::update(
+                    &config.uri,
+                    context.project().path(),
+                    path,
+                    &config.options,
+                    revision,
+                )?
+            }
         }
     }
+
+    Ok(())
 }
 
+/// Remove the destination of the initial download if it was failed.
 struct UpdateGuard<'destination> {
     destination: &'destination Path,
     success: bool,
@@ -81,7 +362,13 @@ impl<'destination> UpdateGuard<'destination> {
 impl<'destination> Drop for UpdateGuard<'destination> {
     fn drop(&mut self) {
         if !self.success {
-            std::fs::remove_dir_all(self.destination).unwrap();
+            // The destination may not exists if we fail before anything has been written. Note that
+            // remove_dir_all will not follow the symbolic link so it is safe for local repository.
+            if let Err(e) = std::fs::remove_dir_all(self.destination) {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    panic!("Failed to remove {}: {}", self.destination.display(), e);
+                }
+            }
         }
     }
 }
@@ -0,0 +1,239 @@
+use serde_yaml::Value;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+
+/// Represents a file that is a part of the application configuration.
+pub struct SourceFile {
+    pub path: PathBuf,
+    pub source: String,
+    pub value: Value,
+}
+
+/// Load the file on `path`, all files it include and its override file, in the order they should be
+/// merged. The `include` key is removed from the loaded values.
+pub fn load(path: &Path) -> Result<Vec<SourceFile>, LoadError> {
+    let mut files = Vec::new();
+    let mut stack = Vec::new();
+
+    load_file(path, &mut stack, &mut files)?;
+
+    let path = override_path(path);
+
+    if path.is_file() {
+        load_file(&path, &mut stack, &mut files)?;
+    }
+
+    Ok(files)
+}
+
+/// Gets the path of the file to override the configuration on `path` (e.g. `locenv-services.override.yml`
+/// for `locenv-services.yml`).
+pub fn override_path(path: &Path) -> PathBuf {
+    let mut name = path.file_stem().unwrap_or_default().to_os_string();
+
+    name.push(".override");
+
+    if let Some(v) = path.extension() {
+        name.push(".");
+        name.push(v);
+    }
+
+    path.with_file_name(name)
+}
+
+fn load_file(
+    path: &Path,
+    stack: &mut Vec<PathBuf>,
+    files: &mut Vec<SourceFile>,
+) -> Result<(), LoadError> {
+    // Read the file.
+    let source =
+        std::fs::read_to_string(path).map_err(|e| LoadError::OpenFailed(path.to_owned(), e))?;
+    let mut value: Value =
+        serde_yaml::from_str(&source).map_err(|e| LoadError::ParseFailed(path.to_owned(), e))?;
+
+    // Load included files first so this file can override them.
+    let includes = match &mut value {
+        Value::Mapping(m) => m.remove(&Value::String("include".into())),
+        _ => None,
+    };
+
+    if let Some(includes) = includes {
+        let includes = match includes {
+            Value::String(v) => vec![v],
+            Value::Sequence(v) => v
+                .into_iter()
+                .map(|v| match v {
+                    Value::String(v) => Ok(v),
+                    _ => Err(LoadError::InvalidInclude(path.to_owned())),
+                })
+                .collect::<Result<_, _>>()?,
+            _ => return Err(LoadError::InvalidInclude(path.to_owned())),
+        };
+
+        let canonical = path.canonicalize().unwrap_or_else(|_| path.to_owned());
+
+        stack.push(canonical);
+
+        for include in includes {
+            let included = path.parent().unwrap_or(Path::new("")).join(include);
+            let canonical = included.canonicalize().unwrap_or_else(|_| included.clone());
+
+            if stack.contains(&canonical) {
+                return Err(LoadError::IncludeCycle(path.to_owned(), included));
+            }
+
+            load_file(&included, stack, files)?;
+        }
+
+        stack.pop();
+    }
+
+    files.push(SourceFile {
+        path: path.to_owned(),
+        source,
+        value,
+    });
+
+    Ok(())
+}
+
+#[derive(Debug)]
+pub enum LoadError {
+    OpenFailed(PathBuf, std::io::Error),
+    ParseFailed(PathBuf, serde_yaml::Error),
+    InvalidInclude(PathBuf),
+    IncludeCycle(PathBuf, PathBuf),
+    InvalidConfiguration(serde_yaml::Error),
+}
+
+impl Error for LoadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::OpenFailed(_, e) => Some(e),
+            Self::ParseFailed(_, e) => Some(e),
+            Self::InvalidConfiguration(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Self::OpenFailed(p, e) => write!(f, "cannot open {}: {}", p.display(), e),
+            Self::ParseFailed(p, e) => write!(f, "cannot parse {}: {}", p.display(), e),
+            Self::InvalidInclude(p) => write!(
+                f,
+                "'include' in {} must be a path or a list of paths",
+                p.display()
+            ),
+            Self::IncludeCycle(p, i) => write!(
+                f,
+                "{} cannot include {} because it cause a cycle",
+                p.display(),
+                i.display()
+            ),
+            Self::InvalidConfiguration(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn override_file() {
+        assert_eq!(
+            override_path(Path::new("/project/locenv-services.yml")),
+            PathBuf::from("/project/locenv-services.override.yml")
+        );
+        assert_eq!(
+            override_path(Path::new("services")),
+            PathBuf::from("services.override")
+        );
+    }
+
+    #[test]
+    fn load_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("locenv-services.yml");
+
+        write(
+            dir.path(),
+            "locenv-services.yml",
+            "include: [a.yml, b.yml]\nname: main\n",
+        );
+        write(dir.path(), "a.yml", "include: common/c.yml\nname: a\n");
+        write(dir.path(), "b.yml", "name: b\n");
+        write(dir.path(), "common/c.yml", "name: c\n");
+        write(
+            dir.path(),
+            "locenv-services.override.yml",
+            "name: override\n",
+        );
+
+        let files = load(&path).unwrap();
+        let names: Vec<&str> = files
+            .iter()
+            .map(|f| f.value["name"].as_str().unwrap())
+            .collect();
+
+        assert_eq!(names, ["c", "a", "b", "main", "override"]);
+        assert!(files.iter().all(|f| f.value.get("include").is_none()));
+        assert_eq!(files[0].path, dir.path().join("common/c.yml"));
+    }
+
+    #[test]
+    fn invalid_include() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("locenv-services.yml");
+
+        write(dir.path(), "locenv-services.yml", "include: [1]\n");
+
+        assert!(matches!(load(&path), Err(LoadError::InvalidInclude(p)) if p == path));
+
+        write(dir.path(), "locenv-services.yml", "include: {a: b}\n");
+
+        assert!(matches!(load(&path), Err(LoadError::InvalidInclude(p)) if p == path));
+    }
+
+    #[test]
+    fn include_cycle() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("locenv-services.yml");
+
+        write(dir.path(), "locenv-services.yml", "include: a.yml\n");
+        write(dir.path(), "a.yml", "include: locenv-services.yml\n");
+
+        match load(&path) {
+            Err(LoadError::IncludeCycle(p, i)) => {
+                assert_eq!(p, dir.path().join("a.yml"));
+                assert_eq!(i, dir.path().join("locenv-services.yml"));
+            }
+            _ => panic!("the cycle is not detected"),
+        }
+    }
+
+    #[test]
+    fn include_not_found() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("locenv-services.yml");
+
+        write(dir.path(), "locenv-services.yml", "include: missing.yml\n");
+
+        assert!(matches!(
+            load(&path),
+            Err(LoadError::OpenFailed(p, _)) if p == dir.path().join("missing.yml")
+        ));
+    }
+
+    fn write(dir: &Path, name: &str, content: &str) {
+        let path = dir.join(name);
+
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+}
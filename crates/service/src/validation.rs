@@ -0,0 +1,565 @@
+use crate::repository::{self, ValidationError};
+use crate::source::{self, LoadError};
+use crate::{ApplicationConfiguration, RepositoryConfigurations, RepositoryType, RepositoryUri};
+use module::PackageId;
+use serde_yaml::{Mapping, Value};
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use yaml::{Location, LocationIndex};
+
+/// Represents a problem in the application configuration.
+#[derive(Debug)]
+pub struct Diagnostic {
+    pub file: PathBuf,
+    pub location: Option<Location>,
+    pub message: String,
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match &self.location {
+            Some(l) => write!(f, "{}:{}: {}", self.file.display(), l, self.message),
+            None => write!(f, "{}: {}", self.file.display(), self.message),
+        }
+    }
+}
+
+/// Check the application configuration on `path`, including all files it include and its override file,
+/// and return all problems that was found. Returns [`Err`] only when `path` itself cannot be read.
+pub fn validate(path: &Path) -> std::io::Result<Vec<Diagnostic>> {
+    // Load all files.
+    let files = match source::load(path) {
+        Ok(r) => r,
+        Err(e) => {
+            let diagnostic = match e {
+                LoadError::OpenFailed(p, e) if p == path => return Err(e),
+                LoadError::OpenFailed(p, e) => Diagnostic {
+                    file: p,
+                    location: None,
+                    message: e.to_string(),
+                },
+                LoadError::ParseFailed(p, e) => Diagnostic {
+                    file: p,
+                    location: e.location().map(|l| Location {
+                        line: l.line(),
+                        column: l.column(),
+                    }),
+                    message: parse_message(&e),
+                },
+                LoadError::InvalidInclude(p) => Diagnostic {
+                    location: locate(&p, &["include"]),
+                    file: p,
+                    message: "'include' must be a path or a list of paths".into(),
+                },
+                LoadError::IncludeCycle(p, i) => Diagnostic {
+                    location: locate(&p, &["include"]),
+                    file: p,
+                    message: format!("including {} cause a cycle", i.display()),
+                },
+                LoadError::InvalidConfiguration(_) => unreachable!(),
+            };
+
+            return Ok(vec![diagnostic]);
+        }
+    };
+
+    // Validate the merged configuration.
+    let mut validator = Validator {
+        indices: Vec::new(),
+        diagnostics: Vec::new(),
+    };
+    let mut root = Value::Null;
+
+    for file in files {
+        if let Ok(index) = LocationIndex::parse(&file.source) {
+            validator.indices.push((file.path, index));
+        }
+
+        ApplicationConfiguration::merge(&mut root, file.value);
+    }
+
+    validator.application(&root);
+
+    let mut diagnostics = validator.diagnostics;
+
+    diagnostics.sort_by(|a, b| {
+        let a = (&a.file, a.location.map(|l| (l.line, l.column)));
+        let b = (&b.file, b.location.map(|l| (l.line, l.column)));
+
+        a.cmp(&b)
+    });
+
+    Ok(diagnostics)
+}
+
+/// Gets the message of `error` without the position since it is already in the diagnostic.
+fn parse_message(error: &serde_yaml::Error) -> String {
+    let message = error.to_string();
+
+    if error.location().is_none() {
+        return message;
+    }
+
+    match message.rfind(" at line ") {
+        Some(i) => message[..i].into(),
+        None => message,
+    }
+}
+
+fn locate(file: &Path, path: &[&str]) -> Option<Location> {
+    let source = std::fs::read_to_string(file).ok()?;
+
+    LocationIndex::parse(&source).ok()?.get(path)
+}
+
+struct Validator {
+    indices: Vec<(PathBuf, LocationIndex)>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Validator {
+    fn application(&mut self, root: &Value) {
+        let root = match self.mapping(&[], root) {
+            Some(v) => v,
+            None => return,
+        };
+
+        self.keys(&[], root, &["configurations", "instances", "modules"]);
+
+        // Configurations.
+        let mut names: Vec<&str> = Vec::new();
+
+        if let Some(configurations) = self.required_mapping(&[], root, "configurations") {
+            for (name, config) in configurations {
+                let name = match name.as_str() {
+                    Some(v) => v,
+                    None => continue,
+                };
+
+                names.push(name);
+                self.configuration(&["configurations", name], config);
+            }
+        }
+
+        // Instances.
+        if let Some(instances) = self.required_mapping(&[], root, "instances") {
+            for (name, instance) in instances {
+                let name = match name.as_str() {
+                    Some(v) => v,
+                    None => continue,
+                };
+
+                self.instance(&["instances", name], instance, &names);
+            }
+        }
+
+        // Modules.
+        if let Some(modules) = root.get(&Value::String("modules".into())) {
+            self.modules(&["modules"], modules);
+        }
+    }
+
+    fn modules(&mut self, path: &[&str], modules: &Value) {
+        let modules = match self.mapping(path, modules) {
+            Some(v) => v,
+            None => return,
+        };
+
+        for (name, id) in modules {
+            let name = match name.as_str() {
+                Some(v) => v,
+                None => continue,
+            };
+
+            match id {
+                Value::String(v) => {
+                    if let Err(e) = v.parse::<PackageId>() {
+                        self.error(
+                            &[path, &[name]].concat(),
+                            format!("'{}' is not a valid package identifier: {}", v, e),
+                        );
+                    }
+                }
+                _ => self.error(
+                    &[path, &[name]].concat(),
+                    "package identifier must be a string",
+                ),
+            }
+        }
+    }
+
+    fn configuration(&mut self, path: &[&str], config: &Value) {
+        let config = match self.mapping(path, config) {
+            Some(v) => v,
+            None => return,
+        };
+
+        self.keys(path, config, &["repository"]);
+
+        if let Some(repository) = self.required_mapping(path, config, "repository") {
+            self.repository(&[path, &["repository"]].concat(), repository);
+        }
+    }
+
+    fn repository(&mut self, path: &[&str], repository: &Mapping) {
+        // Check type.
+        let r#type = match self.required(path, repository, "type") {
+            Some(Value::String(v)) => match v.as_str() {
+                "git" => Some(RepositoryType::Git),
+                "local" => Some(RepositoryType::Local),
+                "archive" => Some(RepositoryType::Archive),
+                _ => {
+                    let candidates = ["git", "local", "archive"];
+                    let message = format!("unknown repository type '{}'", v);
+
+                    self.error(
+                        &[path, &["type"]].concat(),
+                        with_suggestion(message, v, &candidates),
+                    );
+                    None
+                }
+            },
+            Some(_) => {
+                self.error(
+                    &[path, &["type"]].concat(),
+                    "repository type must be a string",
+                );
+                None
+            }
+            None => None,
+        };
+
+        // Check URI.
+        let uri = match self.required(path, repository, "uri") {
+            Some(v) => match serde_yaml::from_value::<RepositoryUri>(v.clone()) {
+                Ok(_) => true,
+                Err(_) => {
+                    self.error(&[path, &["uri"]].concat(), "invalid repository URI");
+                    false
+                }
+            },
+            None => false,
+        };
+
+        // Check options. We can't tell which keys are valid without a valid type.
+        let r#type = match r#type {
+            Some(v) => v,
+            None => return,
+        };
+
+        let options = repository::options(&r#type);
+        let allowed: Vec<&str> = ["uri", "type"].iter().chain(options).cloned().collect();
+
+        self.keys(path, repository, &allowed);
+
+        if !uri {
+            return;
+        }
+
+        let config: RepositoryConfigurations =
+            match serde_yaml::from_value(Value::Mapping(repository.clone())) {
+                Ok(r) => r,
+                Err(_) => return,
+            };
+
+        for e in repository::validate(&config) {
+            match e {
+                ValidationError::InvalidUri => self.error(
+                    &[path, &["uri"]].concat(),
+                    format!(
+                        "'{}' is not supported by {} repository",
+                        config.uri,
+                        type_name(&config.r#type)
+                    ),
+                ),
+                ValidationError::InvalidOption(key) => self.error(
+                    &[path, &[key]].concat(),
+                    format!("invalid value for '{}'", key),
+                ),
+                ValidationError::ArchiveFormatUnknown => self.error(
+                    &[path, &["uri"]].concat(),
+                    "cannot determine archive format from the URI, specify it with 'format'",
+                ),
+            }
+        }
+    }
+
+    fn instance(&mut self, path: &[&str], instance: &Value, configurations: &[&str]) {
+        let instance = match self.mapping(path, instance) {
+            Some(v) => v,
+            None => return,
+        };
+
+        self.keys(path, instance, &["configuration", "profiles"]);
+
+        if let Some(profiles) = instance.get(&Value::String("profiles".into())) {
+            let valid = match profiles {
+                Value::Sequence(v) => v.iter().all(|p| p.is_string()),
+                _ => false,
+            };
+
+            if !valid {
+                self.error(
+                    &[path, &["profiles"]].concat(),
+                    "profiles must be a list of names",
+                );
+            }
+        }
+
+        match self.required(path, instance, "configuration") {
+            Some(Value::String(v)) if !configurations.contains(&v.as_str()) => {
+                let message = format!("configuration '{}' does not exist", v);
+
+                self.error(
+                    &[path, &["configuration"]].concat(),
+                    with_suggestion(message, v, configurations),
+                );
+            }
+            Some(Value::String(_)) => {}
+            Some(_) => self.error(
+                &[path, &["configuration"]].concat(),
+                "configuration must be a string",
+            ),
+            None => {}
+        }
+    }
+
+    /// Report all keys of `mapping` that is not in `allowed`.
+    fn keys(&mut self, path: &[&str], mapping: &Mapping, allowed: &[&str]) {
+        for (key, _) in mapping {
+            let key = match key.as_str() {
+                Some(v) => v,
+                None => {
+                    self.error(path, "key must be a string");
+                    continue;
+                }
+            };
+
+            if !allowed.contains(&key) {
+                let message = format!("unknown key '{}'", key);
+
+                self.error(
+                    &[path, &[key]].concat(),
+                    with_suggestion(message, key, allowed),
+                );
+            }
+        }
+    }
+
+    fn required<'a>(
+        &mut self,
+        path: &[&str],
+        mapping: &'a Mapping,
+        key: &str,
+    ) -> Option<&'a Value> {
+        let value = mapping.get(&Value::String(key.into()));
+
+        if value.is_none() {
+            self.error(path, format!("missing required key '{}'", key));
+        }
+
+        value
+    }
+
+    fn required_mapping<'a>(
+        &mut self,
+        path: &[&str],
+        mapping: &'a Mapping,
+        key: &str,
+    ) -> Option<&'a Mapping> {
+        let value = self.required(path, mapping, key)?;
+
+        self.mapping(&[path, &[key]].concat(), value)
+    }
+
+    fn mapping<'a>(&mut self, path: &[&str], value: &'a Value) -> Option<&'a Mapping> {
+        let mapping = value.as_mapping();
+
+        if mapping.is_none() {
+            self.error(path, "expected a mapping");
+        }
+
+        mapping
+    }
+
+    fn error<M: Into<String>>(&mut self, path: &[&str], message: M) {
+        // Use the location in the file that was merged last since it is where the value come from.
+        let (file, location) = self
+            .indices
+            .iter()
+            .rev()
+            .find_map(|(f, i)| i.get(path).map(|l| (f, Some(l))))
+            .or_else(|| self.indices.last().map(|(f, _)| (f, None)))
+            .unwrap();
+
+        self.diagnostics.push(Diagnostic {
+            file: file.clone(),
+            location,
+            message: message.into(),
+        });
+    }
+}
+
+fn type_name(r#type: &RepositoryType) -> &'static str {
+    match r#type {
+        RepositoryType::Git => "git",
+        RepositoryType::Local => "local",
+        RepositoryType::Archive => "archive",
+    }
+}
+
+fn with_suggestion(message: String, value: &str, candidates: &[&str]) -> String {
+    match suggest(value, candidates) {
+        Some(v) => format!("{}, did you mean '{}'?", message, v),
+        None => message,
+    }
+}
+
+/// Gets the candidate that closest to `value`, if it is close enough to be a typo.
+fn suggest<'a>(value: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    let limit = std::cmp::max(1, value.chars().count() / 3);
+
+    candidates
+        .iter()
+        .map(|c| (*c, distance(value, c)))
+        .filter(|(_, d)| *d <= limit)
+        .min_by_key(|(_, d)| *d)
+        .map(|(c, _)| c)
+}
+
+/// Calculate the edit distance between `a` and `b`, counting a transposition of two adjacent characters
+/// as a single edit.
+fn distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+
+            d[i][j] = (d[i - 1][j - 1] + cost)
+                .min(d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+
+    d[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validate_source(source: &str) -> Vec<Diagnostic> {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("locenv-services.yml");
+
+        std::fs::write(&path, source).unwrap();
+
+        validate(&path).unwrap()
+    }
+
+    #[test]
+    fn parse_error_location() {
+        let diagnostics = validate_source("configurations: {}\ninstances: \"abc\n");
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].location,
+            Some(Location {
+                line: 2,
+                column: 12
+            })
+        );
+        assert_eq!(
+            diagnostics[0].message,
+            "while scanning a quoted scalar, found unexpected end of stream"
+        );
+    }
+
+    #[test]
+    fn unknown_key_location() {
+        let diagnostics = validate_source("configurations: {}\ninstances: {}\nmodule: {}\n");
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].location,
+            Some(Location { line: 3, column: 1 })
+        );
+        assert_eq!(
+            diagnostics[0].message,
+            "unknown key 'module', did you mean 'modules'?"
+        );
+    }
+
+    #[test]
+    fn unknown_repository_type() {
+        let source = "configurations:\n  sample:\n    repository:\n      uri: https://example.com/sample.git\n      type: gti\ninstances: {}\n";
+        let diagnostics = validate_source(source);
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].location,
+            Some(Location { line: 5, column: 7 })
+        );
+        assert_eq!(
+            diagnostics[0].message,
+            "unknown repository type 'gti', did you mean 'git'?"
+        );
+    }
+
+    #[test]
+    fn undefined_configuration() {
+        let source = "configurations: {}\ninstances:\n  sample:\n    configuration: sample\n";
+        let diagnostics = validate_source(source);
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].location,
+            Some(Location { line: 4, column: 5 })
+        );
+        assert_eq!(
+            diagnostics[0].message,
+            "configuration 'sample' does not exist"
+        );
+    }
+
+    #[test]
+    fn valid_configuration() {
+        let source = "configurations:\n  sample:\n    repository:\n      uri: https://example.com/sample.git\n      type: git\ninstances:\n  sample:\n    configuration: sample\n";
+
+        assert!(validate_source(source).is_empty());
+    }
+
+    #[test]
+    fn suggest_closest() {
+        let candidates = ["configurations", "instances", "modules"];
+
+        assert_eq!(suggest("module", &candidates), Some("modules"));
+        assert_eq!(suggest("instnaces", &candidates), Some("instances"));
+        assert_eq!(suggest("foo", &candidates), None);
+    }
+
+    #[test]
+    fn distance_edits() {
+        assert_eq!(distance("git", "git"), 0);
+        assert_eq!(distance("gti", "git"), 1);
+        assert_eq!(distance("gt", "git"), 1);
+        assert_eq!(distance("gitt", "git"), 1);
+        assert_eq!(distance("kitten", "sitting"), 3);
+        assert_eq!(distance("", "abc"), 3);
+    }
+}
@@ -1,8 +1,12 @@
+pub use self::location::{Location, LocationIndex};
+
 use std::error::Error;
 use std::fmt::{Display, Formatter};
 use std::fs::File;
 use std::path::Path;
 
+mod location;
+
 pub fn load_file<P, T>(path: P) -> Result<T, FileError>
 where
     P: AsRef<Path>,
@@ -0,0 +1,217 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use yaml_rust::parser::{Event, MarkedEventReceiver, Parser};
+use yaml_rust::scanner::{Marker, ScanError};
+
+/// Represents a position in the YAML document.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Location {
+    fn from_marker(m: &Marker) -> Self {
+        Self {
+            line: m.line(),
+            column: m.col() + 1,
+        }
+    }
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// Represents the locations of all nodes in the YAML document. The location of a mapping value is
+/// the location of its key.
+pub struct LocationIndex {
+    locations: HashMap<Vec<String>, Location>,
+}
+
+impl LocationIndex {
+    pub fn parse(source: &str) -> Result<Self, (Location, String)> {
+        let mut parser = Parser::new(source.chars());
+        let mut builder = IndexBuilder {
+            locations: HashMap::new(),
+            path: Vec::new(),
+            frames: Vec::new(),
+        };
+
+        parser
+            .load(&mut builder, false)
+            .map_err(|e: ScanError| (Location::from_marker(e.marker()), e.to_string()))?;
+
+        Ok(Self {
+            locations: builder.locations,
+        })
+    }
+
+    /// Gets the location of the node on `path`, which is the list of mapping keys and sequence
+    /// indices from the root.
+    pub fn get(&self, path: &[&str]) -> Option<Location> {
+        let path: Vec<String> = path.iter().map(|v| v.to_string()).collect();
+
+        self.locations.get(&path).cloned()
+    }
+}
+
+struct IndexBuilder {
+    locations: HashMap<Vec<String>, Location>,
+    path: Vec<String>,
+    frames: Vec<Frame>,
+}
+
+enum Frame {
+    Mapping { key: bool },
+    Sequence { index: usize },
+}
+
+impl IndexBuilder {
+    /// Record the location of a value that just started.
+    fn begin_value(&mut self, mark: &Marker) {
+        if let Some(Frame::Sequence { index }) = self.frames.last() {
+            self.path.push(index.to_string());
+        }
+
+        self.locations
+            .entry(self.path.clone())
+            .or_insert_with(|| Location::from_marker(mark));
+    }
+
+    /// Move to the next item of the parent after a value has been completed.
+    fn end_value(&mut self) {
+        match self.frames.last_mut() {
+            Some(Frame::Mapping { key }) => {
+                *key = true;
+                self.path.pop();
+            }
+            Some(Frame::Sequence { index }) => {
+                *index += 1;
+                self.path.pop();
+            }
+            None => {}
+        }
+    }
+}
+
+impl MarkedEventReceiver for IndexBuilder {
+    fn on_event(&mut self, ev: Event, mark: Marker) {
+        // Check if the event is a key of the mapping.
+        if let Some(Frame::Mapping { key }) = self.frames.last_mut() {
+            if *key {
+                *key = false;
+
+                let name = match ev {
+                    Event::Scalar(v, _, _, _) => v,
+                    Event::MappingEnd => {
+                        self.frames.pop();
+                        self.end_value();
+                        return;
+                    }
+                    // We don't support complex key.
+                    _ => String::new(),
+                };
+
+                self.path.push(name);
+                self.locations
+                    .insert(self.path.clone(), Location::from_marker(&mark));
+                return;
+            }
+        }
+
+        match ev {
+            Event::Scalar(_, _, _, _) | Event::Alias(_) => {
+                self.begin_value(&mark);
+                self.end_value();
+            }
+            Event::MappingStart(_) => {
+                self.begin_value(&mark);
+                self.frames.push(Frame::Mapping { key: true });
+            }
+            Event::SequenceStart(_) => {
+                self.begin_value(&mark);
+                self.frames.push(Frame::Sequence { index: 0 });
+            }
+            Event::MappingEnd | Event::SequenceEnd => {
+                self.frames.pop();
+                self.end_value();
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "configurations:
+  sample:
+    repository:
+      uri: https://example.com/sample.git
+      sparse-paths:
+        - a
+        - b
+instances: {sample: {configuration: sample}}
+";
+
+    #[test]
+    fn mapping_keys() {
+        let index = LocationIndex::parse(SOURCE).unwrap();
+
+        assert_eq!(
+            index.get(&["configurations"]),
+            Some(Location { line: 1, column: 1 })
+        );
+        assert_eq!(
+            index.get(&["configurations", "sample", "repository", "uri"]),
+            Some(Location { line: 4, column: 7 })
+        );
+        assert_eq!(
+            index.get(&["instances", "sample", "configuration"]),
+            Some(Location {
+                line: 8,
+                column: 22
+            })
+        );
+    }
+
+    #[test]
+    fn sequence_items() {
+        let index = LocationIndex::parse(SOURCE).unwrap();
+        let path = ["configurations", "sample", "repository", "sparse-paths"];
+
+        assert_eq!(
+            index.get(&[&path[..], &["0"]].concat()),
+            Some(Location {
+                line: 6,
+                column: 11
+            })
+        );
+        assert_eq!(
+            index.get(&[&path[..], &["1"]].concat()),
+            Some(Location {
+                line: 7,
+                column: 11
+            })
+        );
+        assert_eq!(index.get(&[&path[..], &["2"]].concat()), None);
+    }
+
+    #[test]
+    fn missing_path() {
+        let index = LocationIndex::parse(SOURCE).unwrap();
+
+        assert_eq!(index.get(&["modules"]), None);
+    }
+
+    #[test]
+    fn scan_error() {
+        let (location, _) = LocationIndex::parse("a: [\n").err().unwrap();
+
+        assert_eq!(location.line, 2);
+    }
+}